@@ -0,0 +1,284 @@
+use crate::lcg::LCG;
+use crate::utils::{mod_inverse, mul_mod};
+use num::bigint::BigInt;
+use num::integer::{gcd, Integer};
+use num::rational::BigRational;
+use num::{One, Signed, ToPrimitive, Zero};
+
+// multiplier candidates tried when no difference of outputs is invertible modulo m
+const MAX_CANDIDATES: u64 = 1 << 16;
+
+/// Multiple of the modulus of an LCG from consecutive full outputs. With
+/// t_n = x_(n+1) - x_n every t_(n+2) t_n - t_(n+1)^2 is a multiple of m, their gcd is m
+/// itself with high probability once there are about ten outputs, unless all the
+/// differences share a factor with m. `None` when the gcd does not fit into `u64`.
+pub fn recover_modulus(outputs: &[u64]) -> Option<u64> {
+    let differences = outputs
+        .windows(2)
+        .map(|pair| BigInt::from(pair[1]) - BigInt::from(pair[0]))
+        .collect::<Vec<_>>();
+
+    let modulus = differences
+        .windows(3)
+        .map(|t| (&t[2] * &t[0] - &t[1] * &t[1]).abs())
+        .fold(BigInt::zero(), |acc, value| acc.gcd(&value));
+
+    modulus
+        .to_u64()
+        .filter(|m| *m > *outputs.iter().max().unwrap_or(&0))
+}
+
+/// Multiplier and increment for a known modulus from consecutive full outputs. Returns the
+/// generator positioned at the last output, so its next values are the predictions.
+pub fn recover_parameters(outputs: &[u64], modulus: u64) -> Option<LCG> {
+    if outputs.len() < 3 {
+        return None;
+    }
+
+    let m = modulus as i128;
+    let difference = |i: usize| (outputs[i + 1] as i128 - outputs[i] as i128).rem_euclid(m) as u64;
+
+    // t_(i+1) = a t_i mod m, pick the t_i sharing the fewest factors with m
+    let (index, common) = (0..outputs.len() - 2)
+        .map(|i| (i, gcd(difference(i), modulus)))
+        .min_by_key(|(_, common)| *common)?;
+
+    let (current, next) = (difference(index), difference(index + 1));
+    if next % common != 0 || common > MAX_CANDIDATES {
+        return None;
+    }
+
+    // a is only known modulo m / common, every lift is checked against all outputs
+    let reduced = modulus / common;
+    let base = mul_mod(
+        next / common,
+        mod_inverse(current / common, reduced)?,
+        reduced,
+    );
+
+    (0..common)
+        .map(|j| base + j * reduced)
+        .map(|multiplier| {
+            let product = mul_mod(multiplier, outputs[0], modulus);
+            let increment = (outputs[1] as i128 - product as i128).rem_euclid(m) as u64;
+
+            LCG::new(modulus, multiplier, increment, outputs[0])
+        })
+        .find(|lcg| {
+            lcg.clone()
+                .zip(&outputs[1..])
+                .all(|(predicted, actual)| predicted == *actual)
+        })
+        .map(|mut lcg| {
+            lcg.advance(outputs.len() as u64 - 1);
+            lcg
+        })
+}
+
+/// Modulus, multiplier and increment from consecutive full outputs alone: the smallest
+/// divisor of the recovered multiple that is above every output and explains them all.
+pub fn recover(outputs: &[u64]) -> Option<LCG> {
+    let multiple = recover_modulus(outputs)?;
+    let largest = *outputs.iter().max()?;
+    let cofactors = (multiple / (largest + 1)).min(MAX_CANDIDATES);
+
+    (1..=cofactors)
+        .rev()
+        .filter(|d| multiple % d == 0)
+        .find_map(|d| recover_parameters(outputs, multiple / d))
+}
+
+/// State recovery when the parameters are public but only `x >> shift` of every output is
+/// revealed. The unknown low bits are small, so the vector of outputs is close to a lattice
+/// point of (1, a, a^2, ...) + m Z^k: LLL and Babai's nearest plane find it. Returns the
+/// generator positioned at the last output, `None` when the outputs are too few to pin it.
+pub fn recover_truncated(
+    outputs: &[u64],
+    modulus: u64,
+    multiplier: u64,
+    increment: u64,
+    shift: u32,
+) -> Option<LCG> {
+    let k = outputs.len();
+    if k < 2 || shift >= u64::BITS {
+        return None;
+    }
+
+    let m = BigInt::from(modulus);
+    let generator = LCG::new(modulus, multiplier, increment, 0);
+
+    // x_i = a^i x_0 + c_i with c_i the state after i steps from zero
+    let offsets = std::iter::once(0)
+        .chain(generator.clone())
+        .take(k)
+        .map(BigInt::from)
+        .collect::<Vec<_>>();
+
+    // rows m e_i, the first one replaced by (1, a, a^2, ...) mod m
+    let mut basis = (0..k)
+        .map(|i| {
+            let mut row = vec![BigInt::zero(); k];
+            row[i] = m.clone();
+            row
+        })
+        .collect::<Vec<_>>();
+
+    let mut power = BigInt::one();
+    for x in basis[0].iter_mut() {
+        *x = power.clone();
+        power = power * multiplier % &m;
+    }
+
+    // middle of the interval every hidden x_i lies in, minus its offset
+    let half = if shift == 0 {
+        BigInt::zero()
+    } else {
+        BigInt::one() << (shift - 1)
+    };
+    let target = outputs
+        .iter()
+        .zip(&offsets)
+        .map(|(y, c)| (BigInt::from(*y) << shift) + &half - c)
+        .collect::<Vec<_>>();
+
+    lll(&mut basis);
+    let closest = babai(&basis, &target);
+
+    let seed = closest[0].mod_floor(&m).to_u64()?;
+    let lcg = LCG::new(modulus, multiplier, increment, seed);
+
+    let consistent = std::iter::once(seed)
+        .chain(lcg.clone())
+        .zip(outputs)
+        .all(|(x, y)| x >> shift == *y);
+
+    consistent.then(|| {
+        let mut lcg = lcg;
+        lcg.advance(k as u64 - 1);
+        lcg
+    })
+}
+
+// LLL with delta = 3/4 in exact rational arithmetic (Cohen, algorithm 2.6.3), only the
+// squared lengths of the orthogonalized vectors and the mu coefficients are kept
+fn lll(basis: &mut [Vec<BigInt>]) {
+    let n = basis.len();
+    let delta = BigRational::new(BigInt::from(3), BigInt::from(4));
+    let half = BigRational::new(BigInt::one(), BigInt::from(2));
+
+    let (orthogonal, mut mu) = gram_schmidt(basis);
+    let mut lengths = orthogonal.iter().map(|star| norm(star)).collect::<Vec<_>>();
+    let mut k = 1;
+
+    while k < n {
+        for j in (0..k).rev() {
+            if mu[k][j].abs() > half {
+                let q = mu[k][j].round().to_integer();
+
+                let row = basis[j].clone();
+                for (x, y) in basis[k].iter_mut().zip(&row) {
+                    *x -= &q * y;
+                }
+
+                // size reduction keeps the orthogonal vectors, only row k of mu moves
+                let q = BigRational::from(q);
+                let row = mu[j].clone();
+                for (x, y) in mu[k].iter_mut().zip(&row).take(j) {
+                    *x -= &q * y;
+                }
+                mu[k][j] -= &q;
+            }
+        }
+
+        let lovasz = (&delta - &mu[k][k - 1] * &mu[k][k - 1]) * &lengths[k - 1];
+
+        if lengths[k] >= lovasz {
+            k += 1;
+            continue;
+        }
+
+        // swap b_k and b_(k-1) and update the orthogonalization in place
+        basis.swap(k, k - 1);
+
+        let m = mu[k][k - 1].clone();
+        let length = &lengths[k] + &m * &m * &lengths[k - 1];
+
+        mu[k][k - 1] = &m * &lengths[k - 1] / &length;
+        lengths[k] = &lengths[k - 1] * &lengths[k] / &length;
+        lengths[k - 1] = length;
+
+        for j in 0..k - 1 {
+            let (upper, lower) = mu.split_at_mut(k);
+            std::mem::swap(&mut upper[k - 1][j], &mut lower[0][j]);
+        }
+
+        for i in k + 1..n {
+            let t = mu[i][k].clone();
+            mu[i][k] = &mu[i][k - 1] - &m * &t;
+            mu[i][k - 1] = t + &mu[k][k - 1] * &mu[i][k];
+        }
+
+        k = (k - 1).max(1);
+    }
+}
+
+// lattice point near `target` by rounding its coordinates along the orthogonalized basis
+fn babai(basis: &[Vec<BigInt>], target: &[BigInt]) -> Vec<BigInt> {
+    let (orthogonal, _) = gram_schmidt(basis);
+    let mut rest = target
+        .iter()
+        .cloned()
+        .map(BigRational::from)
+        .collect::<Vec<_>>();
+
+    for (row, star) in basis.iter().zip(&orthogonal).rev() {
+        let c = (dot(&rest, star) / norm(star)).round();
+
+        for (x, y) in rest.iter_mut().zip(row) {
+            *x -= &c * BigRational::from(y.clone());
+        }
+    }
+
+    target
+        .iter()
+        .zip(rest)
+        .map(|(t, r)| t - r.to_integer())
+        .collect()
+}
+
+fn gram_schmidt(basis: &[Vec<BigInt>]) -> (Vec<Vec<BigRational>>, Vec<Vec<BigRational>>) {
+    let n = basis.len();
+    let mut orthogonal: Vec<Vec<BigRational>> = Vec::with_capacity(n);
+    let mut mu = vec![vec![BigRational::zero(); n]; n];
+
+    for (i, row) in basis.iter().enumerate() {
+        let row = row
+            .iter()
+            .cloned()
+            .map(BigRational::from)
+            .collect::<Vec<_>>();
+        let mut star = row.clone();
+
+        for j in 0..i {
+            mu[i][j] = dot(&row, &orthogonal[j]) / norm(&orthogonal[j]);
+
+            for (x, y) in star.iter_mut().zip(&orthogonal[j]) {
+                *x -= &mu[i][j] * y;
+            }
+        }
+
+        orthogonal.push(star);
+    }
+
+    (orthogonal, mu)
+}
+
+fn dot(x: &[BigRational], y: &[BigRational]) -> BigRational {
+    x.iter()
+        .zip(y)
+        .fold(BigRational::zero(), |acc, (a, b)| acc + a * b)
+}
+
+fn norm(x: &[BigRational]) -> BigRational {
+    dot(x, x)
+}
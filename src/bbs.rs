@@ -0,0 +1,110 @@
+use crate::generator::{fill_bytes, Generator};
+use crate::utils::is_prime;
+use num::integer::gcd;
+use rand::RngCore;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BbsError {
+    // p has to be a prime congruent to 3 modulo 4
+    NotBlumPrime(u64),
+    EqualPrimes,
+    ModulusOverflow,
+    SeedNotCoprime { seed: u64, modulus: u64 },
+}
+
+impl fmt::Display for BbsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BbsError::NotBlumPrime(p) => write!(f, "{} is not a prime congruent to 3 mod 4", p),
+            BbsError::EqualPrimes => write!(f, "p and q must be different primes"),
+            BbsError::ModulusOverflow => write!(f, "p * q does not fit into 64 bits"),
+            BbsError::SeedNotCoprime { seed, modulus } => {
+                write!(f, "seed {} is not coprime to modulus {}", seed, modulus)
+            }
+        }
+    }
+}
+
+impl Error for BbsError {}
+
+/// Blum Blum Shub: x -> x^2 mod pq, one output bit (the parity of x) per squaring.
+/// Predicting it is as hard as factoring pq, which a 64-bit modulus does not make hard.
+#[derive(Debug, Clone)]
+pub struct Bbs {
+    modulus: u64,
+    state: u64,
+}
+
+impl Bbs {
+    pub fn try_new(p: u64, q: u64, seed: u64) -> Result<Self, BbsError> {
+        for prime in [p, q] {
+            if prime % 4 != 3 || !is_prime(prime) {
+                return Err(BbsError::NotBlumPrime(prime));
+            }
+        }
+
+        if p == q {
+            return Err(BbsError::EqualPrimes);
+        }
+
+        let modulus = p.checked_mul(q).ok_or(BbsError::ModulusOverflow)?;
+
+        if seed % modulus < 2 || gcd(seed, modulus) != 1 {
+            return Err(BbsError::SeedNotCoprime { seed, modulus });
+        }
+
+        // x_0 = s^2 is a quadratic residue, so the sequence is on a pure cycle
+        let mut bbs = Self {
+            modulus,
+            state: seed % modulus,
+        };
+        bbs.square();
+
+        Ok(bbs)
+    }
+
+    fn square(&mut self) {
+        self.state = (self.state as u128 * self.state as u128 % self.modulus as u128) as u64;
+    }
+
+    fn bit(&mut self) -> u64 {
+        self.square();
+
+        self.state & 1
+    }
+}
+
+impl RngCore for Bbs {
+    fn next_u32(&mut self) -> u32 {
+        (0..u32::BITS).fold(0, |value, _| (value << 1) | self.bit() as u32)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        (0..u64::BITS).fold(0, |value, _| (value << 1) | self.bit())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        fill_bytes(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl Generator for Bbs {
+    fn modulus(&self) -> Option<u64> {
+        Some(2)
+    }
+}
+
+impl Iterator for Bbs {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.bit())
+    }
+}
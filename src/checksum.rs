@@ -0,0 +1,224 @@
+use crate::md5::MD5;
+use crate::sha1::Sha1;
+use crate::sha2::{Sha224, Sha256, Sha384, Sha512, Sha512_256};
+use crate::utils::{from_hex, to_hex};
+use std::fmt;
+use std::io::{self, Read};
+use std::str::FromStr;
+
+/// Hash a checksum list was written with, `md5sum`, `sha256sum` and so on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    Md5,
+    Sha1,
+    Sha224,
+    Sha256,
+    Sha384,
+    Sha512,
+    Sha512_256,
+}
+
+impl Algorithm {
+    /// Digest length in bytes.
+    pub fn size(self) -> usize {
+        match self {
+            Algorithm::Md5 => 16,
+            Algorithm::Sha1 => 20,
+            Algorithm::Sha224 => 28,
+            Algorithm::Sha256 | Algorithm::Sha512_256 => 32,
+            Algorithm::Sha384 => 48,
+            Algorithm::Sha512 => 64,
+        }
+    }
+
+    /// Name of the CLI option without the dash, which `from_str` takes back, and the
+    /// program name error messages start with.
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Md5 => "md5",
+            Algorithm::Sha1 => "sha1",
+            Algorithm::Sha224 => "sha224",
+            Algorithm::Sha256 => "sha256",
+            Algorithm::Sha384 => "sha384",
+            Algorithm::Sha512 => "sha512",
+            Algorithm::Sha512_256 => "sha512_256",
+        }
+    }
+
+    pub fn digest(self, input: &[u8]) -> Vec<u8> {
+        match self {
+            Algorithm::Md5 => MD5::digest(input).as_bytes().to_vec(),
+            Algorithm::Sha1 => Sha1::digest(input).as_bytes().to_vec(),
+            Algorithm::Sha224 => Sha224::digest(input).as_bytes().to_vec(),
+            Algorithm::Sha256 => Sha256::digest(input).as_bytes().to_vec(),
+            Algorithm::Sha384 => Sha384::digest(input).as_bytes().to_vec(),
+            Algorithm::Sha512 => Sha512::digest(input).as_bytes().to_vec(),
+            Algorithm::Sha512_256 => Sha512_256::digest(input).as_bytes().to_vec(),
+        }
+    }
+
+    /// Digest of everything `reader` yields, read in chunks in constant memory.
+    pub fn digest_reader<R: Read>(self, reader: R) -> io::Result<Vec<u8>> {
+        let digest = match self {
+            Algorithm::Md5 => MD5::digest_reader(reader)?.as_bytes().to_vec(),
+            Algorithm::Sha1 => Sha1::digest_reader(reader)?.as_bytes().to_vec(),
+            Algorithm::Sha224 => Sha224::digest_reader(reader)?.as_bytes().to_vec(),
+            Algorithm::Sha256 => Sha256::digest_reader(reader)?.as_bytes().to_vec(),
+            Algorithm::Sha384 => Sha384::digest_reader(reader)?.as_bytes().to_vec(),
+            Algorithm::Sha512 => Sha512::digest_reader(reader)?.as_bytes().to_vec(),
+            Algorithm::Sha512_256 => Sha512_256::digest_reader(reader)?.as_bytes().to_vec(),
+        };
+
+        Ok(digest)
+    }
+}
+
+/// Uppercase name, as in the warnings of `md5sum` and `sha256sum`.
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Algorithm::Md5 => write!(f, "MD5"),
+            Algorithm::Sha1 => write!(f, "SHA1"),
+            Algorithm::Sha224 => write!(f, "SHA224"),
+            Algorithm::Sha256 => write!(f, "SHA256"),
+            Algorithm::Sha384 => write!(f, "SHA384"),
+            Algorithm::Sha512 => write!(f, "SHA512"),
+            Algorithm::Sha512_256 => write!(f, "SHA512/256"),
+        }
+    }
+}
+
+/// The name of the CLI option without the dash: `md5`, `sha256`, `sha512_256`.
+impl FromStr for Algorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "md5" => Ok(Algorithm::Md5),
+            "sha1" => Ok(Algorithm::Sha1),
+            "sha224" => Ok(Algorithm::Sha224),
+            "sha256" => Ok(Algorithm::Sha256),
+            "sha384" => Ok(Algorithm::Sha384),
+            "sha512" => Ok(Algorithm::Sha512),
+            "sha512_256" => Ok(Algorithm::Sha512_256),
+            _ => Err(format!("unknown hash '{}'", s)),
+        }
+    }
+}
+
+/// One `md5sum` or `sha256sum` line: the digest and the file it belongs to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub digest: Vec<u8>,
+    pub file: String,
+}
+
+impl Entry {
+    /// `hash  file` or `hash *file`, the star marking binary mode, hex in either case and
+    /// as long as `algorithm` makes it. `None` for anything else.
+    pub fn parse(line: &str, algorithm: Algorithm) -> Option<Self> {
+        let (hash, rest) = line.split_once(' ')?;
+        let file = rest.strip_prefix([' ', '*'])?;
+
+        if file.is_empty() || hash.len() != 2 * algorithm.size() {
+            return None;
+        }
+
+        Some(Entry {
+            digest: from_hex(hash)?,
+            file: String::from(file),
+        })
+    }
+}
+
+/// The line `md5sum` prints for a file, lowercase hex and two spaces.
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}  {}", to_hex(&self.digest), self.file)
+    }
+}
+
+/// Outcome of checking one listed file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    Failed,
+    // the file could not be opened or read
+    Missing,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Ok => write!(f, "OK"),
+            Status::Failed => write!(f, "FAILED"),
+            Status::Missing => write!(f, "FAILED open or read"),
+        }
+    }
+}
+
+/// Tally of a check run, its warnings are worded the way `md5sum -c` words them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Report {
+    pub ok: usize,
+    pub failed: usize,
+    pub missing: usize,
+    pub malformed: usize,
+}
+
+impl Report {
+    pub fn record(&mut self, status: Status) {
+        match status {
+            Status::Ok => self.ok += 1,
+            Status::Failed => self.failed += 1,
+            Status::Missing => self.missing += 1,
+        }
+    }
+
+    /// Every listed file was there and matched, and there was at least one.
+    pub fn passed(&self) -> bool {
+        self.failed == 0 && self.missing == 0 && self.checked() > 0
+    }
+
+    pub fn checked(&self) -> usize {
+        self.ok + self.failed + self.missing
+    }
+
+    pub fn warnings(&self, algorithm: Algorithm) -> Vec<String> {
+        let plural = |count: usize, one: &str, many: &str| {
+            format!("{} {}", count, if count == 1 { one } else { many })
+        };
+
+        if self.checked() == 0 {
+            return vec![format!(
+                "no properly formatted {} checksum lines found",
+                algorithm
+            )];
+        }
+
+        let mut warnings = Vec::new();
+
+        if self.malformed > 0 {
+            warnings.push(format!(
+                "WARNING: {} improperly formatted",
+                plural(self.malformed, "line is", "lines are")
+            ));
+        }
+
+        if self.missing > 0 {
+            warnings.push(format!(
+                "WARNING: {} could not be read",
+                plural(self.missing, "listed file", "listed files")
+            ));
+        }
+
+        if self.failed > 0 {
+            warnings.push(format!(
+                "WARNING: {} did NOT match",
+                plural(self.failed, "computed checksum", "computed checksums")
+            ));
+        }
+
+        warnings
+    }
+}
@@ -0,0 +1,135 @@
+use crate::generator::splitmix64;
+
+/// Largest modulus counted exactly, the bitset then takes at most 16 MiB.
+pub const EXACT_LIMIT: u64 = 1 << 27;
+
+/// HyperLogLog registers are addressed by the top `PRECISION` bits of the hash,
+/// 2^14 of them give a standard error of 1.04 / sqrt(2^14), about 0.8%.
+pub const PRECISION: u32 = 14;
+
+/// One bit per possible value in `0..modulus`, exact.
+#[derive(Debug, Clone)]
+pub struct Bitset {
+    words: Vec<u64>,
+    count: u64,
+}
+
+impl Bitset {
+    pub fn new(modulus: u64) -> Self {
+        Self {
+            words: vec![0; modulus.div_ceil(64) as usize],
+            count: 0,
+        }
+    }
+
+    pub fn insert(&mut self, value: u64) {
+        let word = &mut self.words[(value / 64) as usize];
+        let bit = 1 << (value % 64);
+
+        if *word & bit == 0 {
+            *word |= bit;
+            self.count += 1;
+        }
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+/// Flajolet et al. HyperLogLog, fixed memory whatever the number of values.
+#[derive(Debug, Clone)]
+pub struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    pub fn new() -> Self {
+        Self {
+            registers: vec![0; 1 << PRECISION],
+        }
+    }
+
+    pub fn insert(&mut self, value: u64) {
+        // the SplitMix64 finalizer spreads consecutive values over all the bits
+        let mut state = value;
+        let hash = splitmix64(&mut state);
+
+        let index = (hash >> (64 - PRECISION)) as usize;
+        // position of the first one bit after the index, the sentinel caps it
+        let rank = ((hash << PRECISION) | (1 << (PRECISION - 1))).leading_zeros() as u8 + 1;
+
+        self.registers[index] = self.registers[index].max(rank);
+    }
+
+    pub fn count(&self) -> u64 {
+        let m = self.registers.len() as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+
+        let sum = self
+            .registers
+            .iter()
+            .map(|&rank| 2.0_f64.powi(-(rank as i32)))
+            .sum::<f64>();
+        let estimate = alpha * m * m / sum;
+
+        // linear counting is more accurate while many registers are still empty,
+        // a 64 bit hash needs no correction at the top end
+        let empty = self.registers.iter().filter(|&&rank| rank == 0).count();
+
+        if estimate <= 2.5 * m && empty > 0 {
+            (m * (m / empty as f64).ln()).round() as u64
+        } else {
+            estimate.round() as u64
+        }
+    }
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Counts distinct values of a stream without keeping it: exact for a known modulus up to
+/// `EXACT_LIMIT`, HyperLogLog estimate otherwise.
+#[derive(Debug, Clone)]
+pub enum Distinct {
+    Exact(Bitset),
+    Approximate(HyperLogLog),
+}
+
+impl Distinct {
+    pub fn new(modulus: Option<u64>) -> Self {
+        match modulus {
+            Some(modulus) if modulus <= EXACT_LIMIT => Distinct::Exact(Bitset::new(modulus)),
+            _ => Distinct::Approximate(HyperLogLog::new()),
+        }
+    }
+
+    pub fn insert(&mut self, value: u64) {
+        match self {
+            Distinct::Exact(bitset) => bitset.insert(value),
+            Distinct::Approximate(hll) => hll.insert(value),
+        }
+    }
+
+    pub fn count(&self) -> u64 {
+        match self {
+            Distinct::Exact(bitset) => bitset.count(),
+            Distinct::Approximate(hll) => hll.count(),
+        }
+    }
+
+    pub fn is_exact(&self) -> bool {
+        matches!(self, Distinct::Exact(_))
+    }
+}
+
+impl Extend<u64> for Distinct {
+    fn extend<I: IntoIterator<Item = u64>>(&mut self, values: I) {
+        for value in values {
+            self.insert(value);
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use crate::generator::Generator;
+use crate::stats::ln_gamma;
+use std::f64::consts::PI;
+
+/// Samples from common distributions on top of the uniform bits of any generator.
+#[derive(Debug, Clone)]
+pub struct Sampler<G: Generator> {
+    generator: G,
+    // Box-Muller makes normals in pairs, the second one waits here
+    spare: Option<f64>,
+}
+
+impl<G: Generator> Sampler<G> {
+    pub fn new(generator: G) -> Self {
+        Self {
+            generator,
+            spare: None,
+        }
+    }
+
+    pub fn into_inner(self) -> G {
+        self.generator
+    }
+
+    /// Uniform integer in `low..high`. Values below 2^64 mod n are rejected, so every
+    /// remainder is equally likely, unlike a plain `% n`.
+    pub fn range(&mut self, low: u64, high: u64) -> u64 {
+        assert!(low < high, "Empty range {}..{}", low, high);
+
+        let n = high - low;
+        let threshold = n.wrapping_neg() % n;
+
+        loop {
+            let x = self.generator.next_u64();
+
+            if x >= threshold {
+                return low + x % n;
+            }
+        }
+    }
+
+    /// Uniform float in [0, 1) with all 53 bits of the mantissa random.
+    pub fn uniform(&mut self) -> f64 {
+        (self.generator.next_u64() >> 11) as f64 / (1_u64 << 53) as f64
+    }
+
+    /// Box-Muller transform.
+    pub fn normal(&mut self, mean: f64, std_dev: f64) -> f64 {
+        let z = match self.spare.take() {
+            Some(z) => z,
+            None => {
+                // 1 - u lies in (0, 1], so the logarithm stays finite
+                let radius = (-2.0 * (1.0 - self.uniform()).ln()).sqrt();
+                let angle = 2.0 * PI * self.uniform();
+
+                self.spare = Some(radius * angle.sin());
+                radius * angle.cos()
+            }
+        };
+
+        mean + std_dev * z
+    }
+
+    /// Inversion of the distribution function, `rate` events per unit of time.
+    pub fn exponential(&mut self, rate: f64) -> f64 {
+        -(1.0 - self.uniform()).ln() / rate
+    }
+
+    /// Knuth's product of uniforms for a small mean, Hormann's transformed rejection
+    /// (PTRS) otherwise, which takes a constant number of tries on average.
+    pub fn poisson(&mut self, mean: f64) -> u64 {
+        assert!(mean >= 0.0, "Poisson mean {} is negative", mean);
+
+        if mean < 10.0 {
+            let limit = (-mean).exp();
+            let mut product = self.uniform();
+            let mut count = 0;
+
+            while product > limit {
+                product *= self.uniform();
+                count += 1;
+            }
+
+            return count;
+        }
+
+        let b = 0.931 + 2.53 * mean.sqrt();
+        let a = -0.059 + 0.02483 * b;
+        let inverse_alpha = 1.1239 + 1.1328 / (b - 3.4);
+        let v_r = 0.9277 - 3.6224 / (b - 2.0);
+
+        loop {
+            let u = self.uniform() - 0.5;
+            let v = self.uniform();
+            let us = 0.5 - u.abs();
+            let k = ((2.0 * a / us + b) * u + mean + 0.43).floor();
+
+            if us >= 0.07 && v <= v_r {
+                return k as u64;
+            }
+
+            if k < 0.0 || (us < 0.013 && v > us) {
+                continue;
+            }
+
+            let log_acceptance = v.ln() + inverse_alpha.ln() - (a / (us * us) + b).ln();
+
+            if log_acceptance <= -mean + k * mean.ln() - ln_gamma(k + 1.0) {
+                return k as u64;
+            }
+        }
+    }
+
+    /// Fisher-Yates shuffle, every permutation is equally likely.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.range(0, i as u64 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+
+    /// Algorithm R: `k` items chosen uniformly from a stream of unknown length in one pass.
+    pub fn reservoir<T, I: Iterator<Item = T>>(&mut self, items: I, k: usize) -> Vec<T> {
+        let mut reservoir = Vec::with_capacity(k);
+
+        for (index, item) in items.enumerate() {
+            if index < k {
+                reservoir.push(item);
+            } else {
+                let j = self.range(0, index as u64 + 1) as usize;
+
+                if j < k {
+                    reservoir[j] = item;
+                }
+            }
+        }
+
+        reservoir
+    }
+}
@@ -0,0 +1,25 @@
+use crate::md5::{padding, Digest, Md5};
+
+/// A message the holder of the secret will accept, with the digest they will compute.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Forgery {
+    // message || glue padding || extension, without the secret in front
+    pub message: Vec<u8>,
+    pub digest: Digest,
+}
+
+/// Length extension of MD5(secret || message) by `extension`, knowing only the digest and
+/// the length of the secret. The glue padding is what MD5 appended to secret || message,
+/// so hashing secret || forged message runs through the state the digest captured.
+pub fn forge(digest: Digest, secret_length: usize, message: &[u8], extension: &[u8]) -> Forgery {
+    let glue = padding((secret_length + message.len()) as u64);
+    let processed = (secret_length + message.len() + glue.len()) as u64;
+
+    let mut md5 = Md5::resume(digest, processed);
+    md5.update(extension);
+
+    Forgery {
+        message: [message, &glue, extension].concat(),
+        digest: md5.finalize(),
+    }
+}
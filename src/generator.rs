@@ -0,0 +1,32 @@
+use rand::RngCore;
+
+/// Common interface of the pseudorandom generators: the iterator yields the raw outputs,
+/// `RngCore` turns them into uniform bits for the bit level tests and the `rand` ecosystem.
+pub trait Generator: RngCore + Iterator<Item = u64> + Send {
+    /// Outputs lie in `0..modulus`, `None` means they cover all of `u64`.
+    fn modulus(&self) -> Option<u64>;
+}
+
+/// Output of a generator with the given modulus scaled into [0, 1).
+pub fn to_unit(value: u64, modulus: Option<u64>) -> f64 {
+    value as f64 / modulus.map_or(2.0_f64.powi(64), |m| m as f64)
+}
+
+/// `RngCore::fill_bytes` from whole little endian `next_u64` words.
+pub fn fill_bytes<R: RngCore + ?Sized>(rng: &mut R, dest: &mut [u8]) {
+    for chunk in dest.chunks_mut(8) {
+        let bytes = rng.next_u64().to_le_bytes();
+        chunk.copy_from_slice(&bytes[..chunk.len()]);
+    }
+}
+
+/// SplitMix64 step, used to expand a single `u64` seed into a larger state.
+pub fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e3779b97f4a7c15);
+
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+
+    z ^ (z >> 31)
+}
@@ -0,0 +1,78 @@
+use crate::utils::{constant_time_eq, from_hex, to_base64};
+use std::fmt;
+use std::str::FromStr;
+
+/// `N` byte hash value of MD5, SHA-1 or SHA-2. Equality takes the same time wherever the
+/// first difference is, so comparing against a secret digest leaks nothing through timing.
+#[derive(Debug, Clone, Copy)]
+pub struct Digest<const N: usize>(pub [u8; N]);
+
+impl<const N: usize> Digest<N> {
+    pub fn as_bytes(&self) -> &[u8; N] {
+        &self.0
+    }
+
+    pub fn to_hex(self) -> String {
+        format!("{:x}", self)
+    }
+
+    pub fn to_upper_hex(self) -> String {
+        format!("{:X}", self)
+    }
+
+    /// Standard alphabet with `=` padding, as in RFC 4648.
+    pub fn to_base64(self) -> String {
+        to_base64(&self.0)
+    }
+}
+
+impl<const N: usize> PartialEq for Digest<N> {
+    fn eq(&self, other: &Self) -> bool {
+        constant_time_eq(&self.0, &other.0)
+    }
+}
+
+impl<const N: usize> Eq for Digest<N> {}
+
+impl<const N: usize> From<Digest<N>> for [u8; N] {
+    fn from(digest: Digest<N>) -> Self {
+        digest.0
+    }
+}
+
+impl<const N: usize> fmt::LowerHex for Digest<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|byte| write!(f, "{:02x}", byte))
+    }
+}
+
+impl<const N: usize> fmt::UpperHex for Digest<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|byte| write!(f, "{:02X}", byte))
+    }
+}
+
+/// Uppercase hex, the form `MD5::from` has always returned.
+impl<const N: usize> fmt::Display for Digest<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::UpperHex::fmt(self, f)
+    }
+}
+
+/// `2 * N` hex digits in either case.
+impl<const N: usize> FromStr for Digest<N> {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() != 2 * N || !s.is_ascii() {
+            return Err(format!("expected {} hex digits, got '{}'", 2 * N, s));
+        }
+
+        let bytes = from_hex(s).ok_or_else(|| format!("'{}' is not hexadecimal", s))?;
+
+        let mut digest = [0; N];
+        digest.copy_from_slice(&bytes);
+
+        Ok(Digest(digest))
+    }
+}
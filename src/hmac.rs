@@ -0,0 +1,74 @@
+use crate::utils::constant_time_eq;
+use digest::core_api::BlockSizeUser;
+use digest::{FixedOutput, Output, Update};
+use std::io::{self, Write};
+
+const IPAD: u8 = 0x36;
+const OPAD: u8 = 0x5C;
+
+/// RFC 2104 keyed hash over any block hash of the crate: `Md5`, `Sha1` or SHA-2.
+/// H((K ^ opad) || H((K ^ ipad) || message)), K zero padded to the block size and
+/// hashed first when it is longer than a block.
+#[derive(Debug, Clone)]
+pub struct Hmac<D> {
+    inner: D,
+    outer: D,
+}
+
+impl<D: Update + FixedOutput + BlockSizeUser + Default + Clone> Hmac<D> {
+    pub fn new(key: &[u8]) -> Self {
+        let block_size = D::block_size();
+        let mut block = vec![0_u8; block_size];
+
+        if key.len() > block_size {
+            let hashed = D::default().chain(key).finalize_fixed();
+            block[..hashed.len()].copy_from_slice(&hashed);
+        } else {
+            block[..key.len()].copy_from_slice(key);
+        }
+
+        let pad = |value: u8| block.iter().map(|byte| byte ^ value).collect::<Vec<u8>>();
+
+        Self {
+            inner: D::default().chain(pad(IPAD)),
+            outer: D::default().chain(pad(OPAD)),
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    pub fn finalize(self) -> Output<D> {
+        let inner = self.inner.finalize_fixed();
+
+        self.outer.chain(&inner).finalize_fixed()
+    }
+
+    /// Checks a received tag in constant time.
+    pub fn verify(self, tag: &[u8]) -> bool {
+        constant_time_eq(&self.finalize(), tag)
+    }
+}
+
+/// Lets `io::copy` stream a file or stdin into the tag.
+impl<D: Update + FixedOutput + BlockSizeUser + Default + Clone> Write for Hmac<D> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+pub fn hmac<D: Update + FixedOutput + BlockSizeUser + Default + Clone>(
+    key: &[u8],
+    message: &[u8],
+) -> Output<D> {
+    let mut hmac = Hmac::<D>::new(key);
+    hmac.update(message);
+
+    hmac.finalize()
+}
@@ -1,12 +1,134 @@
-pub struct LCG {
-    modulus: u64,
-    multiplier: u64,
-    increment: u64,
-    seed: u64,
+use crate::generator::Generator;
+use crate::utils::{add_mod, mod_inverse, mul_mod, sub_mod};
+use num::integer::gcd;
+use num::{Integer, PrimInt};
+use rand::{CryptoRng, RngCore, SeedableRng};
+use std::error::Error;
+use std::fmt::{self, Debug, Display};
+use std::iter::{once, Chain, Once};
+use std::str::FromStr;
+
+// Knuth's MMIX multiplier and increment, modulus reduced to 2^63 to fit into u64
+const SEEDABLE_MODULUS: u64 = 1 << 63;
+const SEEDABLE_MULTIPLIER: u64 = 6364136223846793005;
+const SEEDABLE_INCREMENT: u64 = 1442695040888963407;
+
+/// Unsigned integer an `Lcg` keeps its state in: u32, u64 or u128.
+pub trait Word:
+    PrimInt + Integer + From<u32> + Debug + Display + FromStr + Send + Sync + 'static
+{
+    const BITS: u32;
+
+    /// `(a * x + c) mod m` for any operands, without overflow.
+    fn mul_add_mod(a: Self, x: Self, c: Self, m: Self) -> Self;
+}
+
+macro_rules! impl_word {
+    ($typ:tt, $wide:tt) => {
+        impl Word for $typ {
+            const BITS: u32 = $typ::BITS;
+
+            fn mul_add_mod(a: Self, x: Self, c: Self, m: Self) -> Self {
+                // a * x + c fits into the double width word for any operands
+                ((a as $wide * x as $wide + c as $wide) % m as $wide) as $typ
+            }
+        }
+    };
+}
+
+impl_word!(u32, u64);
+impl_word!(u64, u128);
+
+impl Word for u128 {
+    const BITS: u32 = u128::BITS;
+
+    fn mul_add_mod(a: Self, x: Self, c: Self, m: Self) -> Self {
+        let (a, x, c) = (a % m, x % m, c % m);
+
+        // below 2^64 the product still fits
+        if m >> 64 == 0 {
+            return (a * x + c) % m;
+        }
+
+        // double and add, every partial result stays below m
+        let mut result = c;
+        let mut base = a;
+        let mut x = x;
+
+        while x > 0 {
+            if x & 1 == 1 {
+                result = add_mod(result, base, m);
+            }
+
+            base = add_mod(base, base, m);
+            x >>= 1;
+        }
+
+        result
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LcgError {
+    ZeroModulus,
+    SeedOutOfRange { seed: u128, modulus: u128 },
+    // saved state that is not the JSON written by `to_json`
+    InvalidState(String),
 }
 
-impl LCG {
-    pub fn new(modulus: u64, multiplier: u64, increment: u64, seed: u64) -> Self {
+impl fmt::Display for LcgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LcgError::ZeroModulus => write!(f, "modulus must be greater than zero"),
+            LcgError::SeedOutOfRange { seed, modulus } => {
+                write!(f, "seed {} is out of range for modulus {}", seed, modulus)
+            }
+            LcgError::InvalidState(reason) => write!(f, "invalid saved state: {}", reason),
+        }
+    }
+}
+
+impl Error for LcgError {}
+
+/// Hull–Dobell conditions for a mixed LCG to reach the full period `m`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HullDobell {
+    // gcd(c, m) = 1
+    pub increment_coprime: bool,
+    // every prime factor of m divides a - 1
+    pub prime_factors_divide: bool,
+    // 4 divides a - 1 if 4 divides m
+    pub four_divides: bool,
+}
+
+impl HullDobell {
+    pub fn full_period(&self) -> bool {
+        self.increment_coprime && self.prime_factors_divide && self.four_divides
+    }
+}
+
+/// Shape of the state sequence `seed, f(seed), f(f(seed)), ...`: the first `tail`
+/// states are never revisited, after them the sequence repeats every `length` states.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Period {
+    pub tail: u64,
+    pub length: u64,
+}
+
+/// `x -> (a * x + c) mod m` with the state in a `W` word.
+#[derive(Debug, Clone)]
+pub struct Lcg<W: Word> {
+    modulus: W,
+    multiplier: W,
+    increment: W,
+    seed: W,
+}
+
+/// The 64 bit generator used throughout the crate.
+pub type LCG = Lcg<u64>;
+
+impl<W: Word> Lcg<W> {
+    pub fn new(modulus: W, multiplier: W, increment: W, seed: W) -> Self {
         Self {
             modulus,
             multiplier,
@@ -14,13 +136,396 @@ impl LCG {
             seed,
         }
     }
+
+    /// Same as `new`, but rejects a zero modulus and a seed outside `0..modulus`.
+    pub fn try_new(modulus: W, multiplier: W, increment: W, seed: W) -> Result<Self, LcgError> {
+        if modulus.is_zero() {
+            return Err(LcgError::ZeroModulus);
+        }
+
+        if seed >= modulus {
+            return Err(LcgError::SeedOutOfRange {
+                seed: widen(seed),
+                modulus: widen(modulus),
+            });
+        }
+
+        Ok(Self::new(modulus, multiplier, increment, seed))
+    }
+
+    pub fn modulus(&self) -> W {
+        self.modulus
+    }
+
+    pub fn multiplier(&self) -> W {
+        self.multiplier
+    }
+
+    pub fn increment(&self) -> W {
+        self.increment
+    }
+
+    pub fn state(&self) -> W {
+        self.seed
+    }
+
+    /// Steps back to the state before the current one and returns it, so that `next`
+    /// yields the current state again. `None` when the multiplier has no inverse modulo
+    /// `m`: then several states lead to the current one and the state is left as is.
+    pub fn previous(&mut self) -> Option<W> {
+        let m = self.modulus;
+        let inverse = mod_inverse(self.multiplier % m, m)?;
+        let difference = sub_mod(self.seed % m, self.increment % m, m);
+
+        self.seed = mul_mod(inverse, difference, m);
+
+        Some(self.seed)
+    }
+
+    /// Parameters and current state as a small JSON object, `from_json` reads it back.
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\n  \"modulus\": {},\n  \"multiplier\": {},\n  \"increment\": {},\n  \"state\": {}\n}}\n",
+            self.modulus, self.multiplier, self.increment, self.seed
+        )
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, LcgError> {
+        let body = json
+            .trim()
+            .strip_prefix('{')
+            .and_then(|rest| rest.strip_suffix('}'))
+            .ok_or_else(|| LcgError::InvalidState(String::from("expected a JSON object")))?;
+
+        let mut fields = [None; 4];
+        let names = ["modulus", "multiplier", "increment", "state"];
+
+        for entry in body.split(',').filter(|entry| !entry.trim().is_empty()) {
+            let (key, value) = entry.split_once(':').ok_or_else(|| {
+                LcgError::InvalidState(format!("malformed entry {}", entry.trim()))
+            })?;
+
+            let key = key.trim().trim_matches('"');
+            let index = names
+                .iter()
+                .position(|name| *name == key)
+                .ok_or_else(|| LcgError::InvalidState(format!("unknown field {}", key)))?;
+
+            let value = value
+                .trim()
+                .parse::<W>()
+                .map_err(|_| LcgError::InvalidState(format!("{} is not a u{}", key, W::BITS)))?;
+
+            fields[index] = Some(value);
+        }
+
+        match fields {
+            [Some(modulus), Some(multiplier), Some(increment), Some(state)] => {
+                Self::try_new(modulus, multiplier, increment, state)
+            }
+            _ => {
+                let missing = names
+                    .iter()
+                    .zip(fields)
+                    .find(|(_, value)| value.is_none())
+                    .map(|(name, _)| *name)
+                    .unwrap_or_default();
+
+                Err(LcgError::InvalidState(format!("missing field {}", missing)))
+            }
+        }
+    }
+
+    pub fn hull_dobell(&self) -> HullDobell {
+        let m = self.modulus;
+        let one = W::one();
+        let four: W = 4.into();
+        // a - 1 reduced modulo m, so that a = 0 does not underflow
+        let a_minus_one = sub_mod(self.multiplier % m, one % m, m);
+
+        // strip from m every prime it shares with a - 1, what is left has to be 1
+        let mut rest = if a_minus_one.is_zero() { one } else { m };
+        let mut common = gcd(rest, a_minus_one);
+        while common > one {
+            rest = rest / common;
+            common = gcd(rest, a_minus_one);
+        }
+
+        HullDobell {
+            increment_coprime: gcd(self.increment, m) == one,
+            prime_factors_divide: rest == one,
+            four_divides: !m.is_multiple_of(&four) || a_minus_one.is_multiple_of(&four),
+        }
+    }
+
+    /// Finds the tail and cycle length starting from the current state with Brent's
+    /// algorithm, so only two states are kept at a time. Takes O(tail + length) steps.
+    pub fn period(&self) -> Period {
+        self.period_within(u64::MAX)
+            .expect("Cycle longer than 2^64 states")
+    }
+
+    /// `period`, but `None` once the hare has taken `max_steps` steps without closing
+    /// the cycle, which Brent's algorithm does within 3 * (tail + length) steps. Full
+    /// period parameters are answered by Hull–Dobell without stepping.
+    pub fn period_within(&self, max_steps: u64) -> Option<Period> {
+        let start = self.seed;
+
+        if start < self.modulus && self.hull_dobell().full_period() {
+            if let Some(length) = self.modulus.to_u64() {
+                return Some(Period { tail: 0, length });
+            }
+        }
+
+        // find the cycle length: the tortoise teleports to the hare at powers of two
+        let mut power = 1;
+        let mut length = 1;
+        let mut steps = 1;
+        let mut tortoise = start;
+        let mut hare = self.step(start);
+
+        while tortoise != hare {
+            if steps == max_steps {
+                return None;
+            }
+
+            if power == length {
+                tortoise = hare;
+                power *= 2;
+                length = 0;
+            }
+
+            hare = self.step(hare);
+            length += 1;
+            steps += 1;
+        }
+
+        // find the tail: move a second pointer `length` states ahead and walk both
+        let mut tortoise = start;
+        let mut hare = start;
+
+        for _ in 0..length {
+            hare = self.step(hare);
+        }
+
+        let mut tail = 0;
+
+        while tortoise != hare {
+            tortoise = self.step(tortoise);
+            hare = self.step(hare);
+            tail += 1;
+        }
+
+        Some(Period { tail, length })
+    }
+
+    /// Skips `steps` states in O(log steps) multiplications.
+    pub fn advance(&mut self, steps: u64) {
+        let (multiplier, increment) = self.jump(steps);
+
+        self.seed = self.affine(multiplier, increment, self.seed);
+    }
+
+    /// Block splitting: stream `i` starts `i * block` states ahead of this generator,
+    /// so the streams do not overlap as long as each one takes at most `block` values.
+    pub fn split(&self, streams: usize, block: u64) -> Vec<Lcg<W>> {
+        let (multiplier, increment) = self.jump(block);
+
+        let mut current = self.clone();
+        let mut result = Vec::with_capacity(streams);
+
+        for _ in 0..streams {
+            result.push(current.clone());
+            current.seed = self.affine(multiplier, increment, current.seed);
+        }
+
+        result
+    }
+
+    /// Leapfrog splitting: stream `i` yields values `i, i + k, i + 2k, ...` of this
+    /// generator's output, where `k` is the number of streams.
+    pub fn leapfrog(&self, streams: usize) -> Vec<Chain<Once<W>, Lcg<W>>> {
+        let (multiplier, increment) = self.jump(streams as u64);
+
+        let mut state = self.seed;
+        let mut result = Vec::with_capacity(streams);
+
+        for _ in 0..streams {
+            let first = self.step(state);
+            state = first;
+
+            let stream = Lcg::new(self.modulus, multiplier, increment, first);
+            result.push(once(first).chain(stream));
+        }
+
+        result
+    }
+
+    // coefficients of x -> a * x + c composed `steps` times, by repeated squaring
+    fn jump(&self, mut steps: u64) -> (W, W) {
+        let m = self.modulus;
+
+        let mut multiplier = W::one() % m;
+        let mut increment = W::zero();
+        let mut base_multiplier = self.multiplier % m;
+        let mut base_increment = self.increment % m;
+
+        while steps > 0 {
+            if steps & 1 == 1 {
+                multiplier = mul_mod(base_multiplier, multiplier, m);
+                increment = self.affine(base_multiplier, base_increment, increment);
+            }
+
+            base_increment = self.affine(base_multiplier, base_increment, base_increment);
+            base_multiplier = mul_mod(base_multiplier, base_multiplier, m);
+
+            steps >>= 1;
+        }
+
+        (multiplier, increment)
+    }
+
+    // `bits` random bits built from as many outputs as needed. Low bits of an LCG
+    // modulo a power of two have short periods, so at most the top 32 uniform bits
+    // of every output are used.
+    fn extract(&mut self, bits: u32) -> u64 {
+        let uniform = self.uniform_bits();
+        let chunk = uniform.min(32);
+        let mut value = 0;
+        let mut filled = 0;
+
+        while filled < bits {
+            let top = self.next_uniform() >> (uniform - chunk) as usize;
+
+            // at most 32 bits, which fit into u64 for any width
+            value = (value << chunk) | top.to_u64().unwrap_or_default();
+            filled += chunk;
+        }
+
+        if bits == u64::BITS {
+            value
+        } else {
+            value & ((1 << bits) - 1)
+        }
+    }
+
+    // floor(log2(m)): outputs below 2^bits are uniform over all `bits` low bits
+    fn uniform_bits(&self) -> u32 {
+        assert!(
+            self.modulus > W::one(),
+            "Modulus {} is too small to produce random bits",
+            self.modulus
+        );
+
+        W::BITS - 1 - self.modulus.leading_zeros()
+    }
+
+    // rejection sampling instead of truncation, which would bias the top bits when
+    // the modulus is not a power of two
+    fn next_uniform(&mut self) -> W {
+        let bits = self.uniform_bits();
+
+        loop {
+            let value = self.step(self.seed);
+            self.seed = value;
+
+            if (value >> bits as usize).is_zero() {
+                return value;
+            }
+        }
+    }
+
+    fn step(&self, state: W) -> W {
+        self.affine(self.multiplier, self.increment, state)
+    }
+
+    fn affine(&self, multiplier: W, increment: W, state: W) -> W {
+        W::mul_add_mod(multiplier, state, increment, self.modulus)
+    }
+}
+
+/// Raw bits come from rejection sampled outputs, so an LCG whose cycle never drops below
+/// the largest power of two under its modulus will spin forever.
+impl<W: Word> RngCore for Lcg<W> {
+    fn next_u32(&mut self) -> u32 {
+        self.extract(u32::BITS) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.extract(u64::BITS)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Seeds a full-period mod 2^63 generator with Knuth's MMIX multiplier and increment.
+impl SeedableRng for Lcg<u64> {
+    type Seed = [u8; 8];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        Self::seed_from_u64(u64::from_le_bytes(seed))
+    }
+
+    fn seed_from_u64(state: u64) -> Self {
+        Self::new(
+            SEEDABLE_MODULUS,
+            SEEDABLE_MULTIPLIER,
+            SEEDABLE_INCREMENT,
+            state % SEEDABLE_MODULUS,
+        )
+    }
+}
+
+/// Claims `CryptoRng` for any generator so it can be passed to the `rsa` and `dsa` key
+/// generators in reproducible demos. The output of an LCG is predictable, keys made
+/// this way must never be used for anything real.
+pub struct Insecure<R>(pub R);
+
+impl<R: RngCore> RngCore for Insecure<R> {
+    fn next_u32(&mut self) -> u32 {
+        self.0.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.0.try_fill_bytes(dest)
+    }
+}
+
+impl<R: RngCore> CryptoRng for Insecure<R> {}
+
+fn widen<W: Word>(value: W) -> u128 {
+    value.to_u128().unwrap_or_default()
+}
+
+impl Generator for Lcg<u64> {
+    fn modulus(&self) -> Option<u64> {
+        Some(self.modulus)
+    }
 }
 
-impl Iterator for LCG {
-    type Item = u64;
+impl<W: Word> Iterator for Lcg<W> {
+    type Item = W;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.seed = (self.multiplier * self.seed + self.increment) % self.modulus;
+        self.seed = self.step(self.seed);
 
         Some(self.seed)
     }
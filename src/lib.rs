@@ -1,13 +1,37 @@
-mod lcg;
-mod md5;
-mod rc5;
-mod utils;
+pub mod attack;
+pub mod bbs;
+pub mod checksum;
+pub mod distinct;
+pub mod distributions;
+pub mod extension;
+pub mod generator;
+mod hash;
+pub mod hmac;
+pub mod lcg;
+pub mod md5;
+pub mod mt19937;
+pub mod multibuffer;
+pub mod nist;
+pub mod output;
+pub mod pcg;
+pub mod plot;
+pub mod presets;
+pub mod rc5;
+pub mod sha1;
+pub mod sha2;
+pub mod spectral;
+pub mod stats;
+pub mod utils;
+pub mod xorshift;
 
 #[cfg(test)]
 mod core {
     mod lcg {
-        use crate::lcg::LCG;
+        use crate::lcg::{Insecure, Lcg, LcgError, Period, LCG};
+        use crate::presets::{GlibcRand, Preset, PRESETS};
         use crate::utils::unique;
+        use rand::{Rng, RngCore, SeedableRng};
+        use rsa::RsaPrivateKey;
 
         #[test]
         fn lcg_lecture() {
@@ -35,24 +59,1048 @@ mod core {
             let increment = 1;
             let seed = 4;
 
-            let mut lcg = LCG::new(modulus, multiplier, increment, seed);
+            let mut lcg = LCG::new(modulus, multiplier, increment, seed);
+
+            let mut nums = Vec::<u64>::with_capacity(1000);
+            for _ in 0..1000 {
+                let num = lcg.next().expect("Failed generate next number!");
+
+                nums.push(num);
+            }
+
+            println!("{:?}", nums);
+            println!("{}", unique(&nums));
+
+            assert_eq!(nums.len(), 1000);
+            assert!(unique(&nums) >= 50);
+        }
+
+        #[test]
+        fn lcg_large_parameters() {
+            let modulus = 1 << 63;
+            let multiplier = 6364136223846793005;
+            let increment = 1442695040888963407;
+            let seed = (1 << 63) - 1;
+
+            let mut lcg =
+                LCG::try_new(modulus, multiplier, increment, seed).expect("Failed to create LCG");
+
+            let expected =
+                ((multiplier as u128 * seed as u128 + increment as u128) % modulus as u128) as u64;
+
+            assert_eq!(lcg.next(), Some(expected));
+        }
+
+        #[test]
+        fn lcg_invalid_parameters() {
+            assert_eq!(LCG::try_new(0, 7, 0, 1).err(), Some(LcgError::ZeroModulus));
+            assert_eq!(
+                LCG::try_new(32, 7, 0, 32).err(),
+                Some(LcgError::SeedOutOfRange {
+                    seed: 32,
+                    modulus: 32
+                })
+            );
+        }
+
+        #[test]
+        fn lcg_hull_dobell() {
+            // Knuth MMIX constants reduced to 2^63
+            let full = LCG::new(1 << 63, 6364136223846793005, 1442695040888963407, 0);
+            assert!(full.hull_dobell().full_period());
+
+            // 2047 = 23 * 89, but 243 - 1 is divisible by neither
+            let lecture = LCG::new(2047, 243, 1, 4);
+            assert!(!lecture.hull_dobell().prime_factors_divide);
+            assert!(!lecture.hull_dobell().full_period());
+
+            // m = 32 requires 4 | a - 1, a = 7 fails it
+            let power = LCG::new(32, 7, 1, 0);
+            assert!(!power.hull_dobell().four_divides);
+
+            let mut counter = LCG::new(32, 5, 3, 0);
+            assert!(counter.hull_dobell().full_period());
+
+            let mut nums = (0..32).map(|_| counter.next().unwrap()).collect::<Vec<_>>();
+            nums.sort();
+            assert_eq!(nums, (0..32).collect::<Vec<u64>>());
+        }
+
+        #[test]
+        fn lcg_period() {
+            let lecture = LCG::new(1 << 5, 7, 0, 1);
+            assert_eq!(lecture.period(), Period { tail: 0, length: 4 });
+
+            let own = LCG::new((1 << 11) - 1, 3_u64.pow(5), 1, 4);
+            assert_eq!(
+                own.period(),
+                Period {
+                    tail: 0,
+                    length: 88
+                }
+            );
+
+            // 1, 2, 4, 8, 16, 0, 0, ...
+            let doubling = LCG::new(32, 2, 0, 1);
+            assert_eq!(doubling.period(), Period { tail: 5, length: 1 });
+        }
+
+        #[test]
+        fn lcg_period_within() {
+            // full period by Hull–Dobell, answered without walking 2^63 states
+            let full = LCG::new(1 << 63, 6364136223846793005, 1, 0);
+            assert_eq!(
+                full.period_within(1000),
+                Some(Period {
+                    tail: 0,
+                    length: 1 << 63
+                })
+            );
+
+            // what the CLI makes of 2^63: not full period and far too long to walk
+            let long = LCG::new((1 << 63) - 1, 6364136223846793005, 1, 0);
+            assert_eq!(long.period_within(1 << 16), None);
+
+            let own = LCG::new((1 << 11) - 1, 3_u64.pow(5), 1, 4);
+            assert_eq!(own.period_within(1 << 16), Some(own.period()));
+            assert_eq!(own.period_within(10), None);
+        }
+
+        #[test]
+        fn lcg_advance() {
+            let lcg = LCG::new((1 << 31) - 1, 16807, 0, 1);
+            let sequence = lcg.clone().take(1000).collect::<Vec<_>>();
+
+            for steps in [0, 1, 2, 17, 999] {
+                let mut jumped = lcg.clone();
+                jumped.advance(steps);
+
+                assert_eq!(jumped.next(), Some(sequence[steps as usize]));
+            }
+
+            // a whole cycle brings the generator back to its seed
+            let mut lecture = LCG::new((1 << 11) - 1, 3_u64.pow(5), 1, 4);
+            lecture.advance(88 * 1_000_000_007);
+            assert_eq!(lecture.state(), 4);
+        }
+
+        #[test]
+        fn lcg_split() {
+            let lcg = LCG::new(1 << 63, 6364136223846793005, 1442695040888963407, 42);
+            let sequence = lcg.clone().take(120).collect::<Vec<_>>();
+
+            let blocks = lcg
+                .split(4, 30)
+                .into_iter()
+                .flat_map(|stream| stream.take(30))
+                .collect::<Vec<_>>();
+            assert_eq!(blocks, sequence);
+
+            let mut streams = lcg.leapfrog(3);
+            let mut interleaved = Vec::new();
+            for _ in 0..40 {
+                for stream in streams.iter_mut() {
+                    interleaved.push(stream.next().unwrap());
+                }
+            }
+            assert_eq!(interleaved, sequence);
+        }
+
+        #[test]
+        fn lcg_previous() {
+            let mut lcg = LCG::new((1 << 31) - 1, 48271, 11, 5);
+            let forward = lcg.by_ref().take(100).collect::<Vec<_>>();
+
+            // stepping back visits the same states in reverse, ending at the seed
+            let mut backward = (0..100)
+                .map(|_| lcg.previous().unwrap())
+                .collect::<Vec<_>>();
+            backward.reverse();
+
+            assert_eq!(backward[0], 5);
+            assert_eq!(&backward[1..], &forward[..99]);
+            assert_eq!(lcg.next(), Some(forward[0]));
+
+            // an even multiplier modulo 2^5 merges states, there is no way back
+            let mut lcg = LCG::new(1 << 5, 6, 1, 3);
+            assert_eq!(lcg.previous(), None);
+            assert_eq!(lcg.state(), 3);
+        }
+
+        #[test]
+        fn lcg_json_state() {
+            let mut lcg = LCG::seed_from_u64(99);
+            lcg.advance(1000);
+
+            let mut resumed = LCG::from_json(&lcg.to_json()).unwrap();
+            assert_eq!(
+                resumed.by_ref().take(10).collect::<Vec<_>>(),
+                lcg.take(10).collect::<Vec<_>>()
+            );
+
+            let json = r#"{"state": 4, "increment": 1, "multiplier": 243, "modulus": 2047}"#;
+            assert_eq!(LCG::from_json(json).unwrap().next(), Some(973));
+
+            assert!(matches!(
+                LCG::from_json(r#"{"modulus": 2047, "multiplier": 243, "increment": 1}"#),
+                Err(LcgError::InvalidState(_))
+            ));
+            assert!(matches!(
+                LCG::from_json(r#"{"modulus": -1, "multiplier": 243}"#),
+                Err(LcgError::InvalidState(_))
+            ));
+            assert_eq!(
+                LCG::from_json(r#"{"modulus": 7, "multiplier": 3, "increment": 1, "state": 9}"#)
+                    .unwrap_err(),
+                LcgError::SeedOutOfRange {
+                    seed: 9,
+                    modulus: 7
+                }
+            );
+        }
+
+        #[test]
+        fn lcg_rng_core() {
+            // 2047 leaves 10 uniform bits per output, values >= 1024 are skipped
+            let lcg = LCG::new((1 << 11) - 1, 3_u64.pow(5), 1, 4);
+            let chunks = lcg
+                .clone()
+                .filter(|value| *value < 1024)
+                .take(4)
+                .collect::<Vec<_>>();
+            let expected = chunks.iter().fold(0_u64, |acc, chunk| (acc << 10) | chunk);
+
+            assert_eq!(lcg.clone().next_u32(), expected as u32);
+
+            let mut bytes = [0_u8; 11];
+            let mut rng = LCG::seed_from_u64(7);
+            rng.fill_bytes(&mut bytes);
+
+            let mut same = LCG::from_seed(7_u64.to_le_bytes());
+            assert_eq!(&bytes[..8], &same.next_u64().to_le_bytes());
+            assert_eq!(&bytes[8..], &same.next_u64().to_le_bytes()[..3]);
+
+            let dice = (0..1000).map(|_| rng.gen_range(1..=6)).collect::<Vec<u8>>();
+            assert!((1..=6).all(|side| dice.contains(&side)));
+        }
+
+        #[test]
+        fn lcg_reproducible_keys() {
+            let first = RsaPrivateKey::new(&mut Insecure(LCG::seed_from_u64(42)), 512)
+                .expect("Failed to generate key");
+            let second = RsaPrivateKey::new(&mut Insecure(LCG::seed_from_u64(42)), 512)
+                .expect("Failed to generate key");
+
+            assert_eq!(first, second);
+        }
+
+        #[test]
+        fn lcg_widths() {
+            let narrow = Lcg::<u32>::new((1 << 31) - 1, 48271, 11, 5);
+            let wide = Lcg::<u128>::new((1 << 31) - 1, 48271, 11, 5);
+            let sequence = LCG::new((1 << 31) - 1, 48271, 11, 5)
+                .take(100)
+                .collect::<Vec<u64>>();
+
+            assert_eq!(
+                narrow.clone().take(100).map(u64::from).collect::<Vec<_>>(),
+                sequence
+            );
+            assert_eq!(
+                wide.take(100).map(|x| x as u64).collect::<Vec<_>>(),
+                sequence
+            );
+
+            // the largest u32 operands do not overflow the intermediate product
+            let mut top = Lcg::<u32>::new(u32::MAX, u32::MAX - 1, u32::MAX - 1, u32::MAX - 2);
+            assert_eq!(
+                top.next(),
+                Some(((u32::MAX as u64 - 1).pow(2) % u32::MAX as u64) as u32)
+            );
+
+            assert_eq!(
+                Lcg::<u32>::new(1 << 5, 7, 0, 1).period(),
+                Period { tail: 0, length: 4 }
+            );
+            assert!(Lcg::<u32>::new(1 << 31, 1103515245, 12345, 1)
+                .hull_dobell()
+                .full_period());
+        }
+
+        #[test]
+        fn lcg_u128() {
+            let m = (1 << 127) - 1;
+            let mut lcg = Lcg::<u128>::new(
+                m,
+                0x2360ED051FC65DA44385DF649FCCF645,
+                1442695040888963407,
+                m - 2,
+            );
+
+            assert_eq!(
+                lcg.clone().take(3).collect::<Vec<_>>(),
+                vec![
+                    76088688084584988036841583773098022084,
+                    141086801320389727797706259644655584612,
+                    22361556768578762310171896982608199917
+                ]
+            );
+
+            let mut jumped = lcg.clone();
+            jumped.advance(2);
+            assert_eq!(jumped.state(), 141086801320389727797706259644655584612);
+
+            assert_eq!(lcg.next(), Some(76088688084584988036841583773098022084));
+            assert_eq!(lcg.previous(), Some(m - 2));
+
+            let resumed = Lcg::<u128>::from_json(&lcg.to_json()).unwrap();
+            assert_eq!(resumed.state(), m - 2);
+            assert!(matches!(
+                LCG::from_json(&lcg.to_json()),
+                Err(LcgError::InvalidState(_))
+            ));
+        }
+
+        // rand() of glibc after srand(seed), srand(0) means srand(1)
+        #[test]
+        fn glibc_rand() {
+            let outputs = |seed: u32| GlibcRand::new(seed).take(3).collect::<Vec<_>>();
+
+            assert_eq!(outputs(1), vec![1804289383, 846930886, 1681692777]);
+            assert_eq!(outputs(0), outputs(1));
+            assert_eq!(outputs(42), vec![71876166, 708592740, 1483128881]);
+            assert_eq!(
+                outputs(3_000_000_000),
+                vec![2058147116, 854483408, 922419988]
+            );
+        }
+
+        #[test]
+        fn lcg_presets() {
+            let outputs = |name: &str, seed: u64, count: usize| {
+                let preset = Preset::find(name).unwrap();
+
+                match preset.width() {
+                    32 => preset
+                        .generator::<u32>(seed)
+                        .take(count)
+                        .collect::<Vec<_>>(),
+                    64 => preset
+                        .generator::<u64>(seed)
+                        .take(count)
+                        .collect::<Vec<_>>(),
+                    _ => preset
+                        .generator::<u128>(seed)
+                        .take(count)
+                        .collect::<Vec<_>>(),
+                }
+            };
+
+            assert_eq!(outputs("msvc", 1, 3), vec![41, 18467, 6334]);
+            assert_eq!(outputs("minstd", 1, 2), vec![16807, 282475249]);
+            // the 10000th output of minstd_rand is the value C++11 requires
+            assert_eq!(outputs("minstd_rand", 1, 10000)[9999], 399268537);
+            // minstd_rand0(0) of C++ starts from 1
+            assert_eq!(outputs("minstd", 0, 2), vec![16807, 282475249]);
+            assert_eq!(
+                outputs("glibc_type0", 1, 3),
+                vec![1103527590, 377401575, 662824084]
+            );
+            assert_eq!(outputs("nr", 0, 2), vec![1013904223, 1196435762]);
+            assert_eq!(
+                outputs("mmix", 1, 2),
+                vec![7806831264735756412, 9396908728118811419]
+            );
+
+            // new Random(42).nextInt() in Java
+            let java = outputs("java", 42, 2);
+            assert_eq!(java[0] as u32 as i32, -1170105035);
+            assert_eq!(java[1] as u32 as i32, 234785527);
+
+            assert_eq!(Preset::find("mmix").unwrap().width(), 128);
+            assert_eq!(Preset::find("java").unwrap().width(), 64);
+            assert!(Preset::find("randu").is_none());
+            assert!(Preset::find("glibc").is_none());
+            assert!(PRESETS
+                .iter()
+                .all(|preset| Preset::find(preset.name) == Some(preset)));
+        }
+    }
+    mod attack {
+        use crate::attack::{recover, recover_modulus, recover_parameters, recover_truncated};
+        use crate::lcg::LCG;
+        use rand::SeedableRng;
+
+        #[test]
+        fn full_outputs() {
+            let outputs = LCG::seed_from_u64(31337).take(10).collect::<Vec<_>>();
+            assert_eq!(recover_modulus(&outputs), Some(1 << 63));
+
+            let mut cracked = recover(&outputs).expect("Unable to recover the LCG");
+            let actual = LCG::seed_from_u64(31337)
+                .skip(10)
+                .take(5)
+                .collect::<Vec<_>>();
+
+            assert_eq!(cracked.multiplier(), 6364136223846793005);
+            assert_eq!(cracked.increment(), 1442695040888963407);
+            assert_eq!(cracked.by_ref().take(5).collect::<Vec<_>>(), actual);
+        }
+
+        #[test]
+        fn rc5_iv_generator() {
+            // the CBC initialization vector generator of rc5, its first outputs leak the rest
+            let outputs = LCG::new(65538, 75, 74, 0).take(12).collect::<Vec<_>>();
+            let cracked = recover(&outputs).expect("Unable to recover the LCG");
+
+            assert_eq!(cracked.modulus(), 65538);
+            assert_eq!(
+                cracked.take(20).collect::<Vec<_>>(),
+                LCG::new(65538, 75, 74, 0)
+                    .skip(12)
+                    .take(20)
+                    .collect::<Vec<_>>()
+            );
+        }
+
+        #[test]
+        fn known_modulus() {
+            let outputs = LCG::new((1 << 31) - 1, 48271, 0, 42)
+                .take(3)
+                .collect::<Vec<_>>();
+            let cracked = recover_parameters(&outputs, (1 << 31) - 1).unwrap();
+
+            assert_eq!((cracked.multiplier(), cracked.increment()), (48271, 0));
+            assert_eq!(
+                recover_parameters(&outputs[..2], (1 << 31) - 1).map(|_| ()),
+                None
+            );
+        }
+
+        #[test]
+        fn truncated_outputs() {
+            let (m, a, c) = (1 << 63, 6364136223846793005, 1442695040888963407);
+            let lcg = LCG::new(m, a, c, 987654321987654321);
+
+            // only the top 31 of 63 bits of every state are revealed
+            let outputs = lcg.clone().take(6).map(|x| x >> 32).collect::<Vec<_>>();
+            let cracked = recover_truncated(&outputs, m, a, c, 32).expect("Unable to recover");
+
+            assert_eq!(
+                cracked.take(10).collect::<Vec<_>>(),
+                lcg.skip(6).take(10).collect::<Vec<_>>()
+            );
+        }
+    }
+    mod distributions {
+        use crate::distributions::Sampler;
+        use crate::lcg::LCG;
+        use crate::stats::chi_square;
+        use crate::xorshift::Xoshiro256;
+        use rand::SeedableRng;
+
+        fn moments(samples: &[f64]) -> (f64, f64) {
+            let n = samples.len() as f64;
+            let mean = samples.iter().sum::<f64>() / n;
+            let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n;
+
+            (mean, variance)
+        }
+
+        #[test]
+        fn unbiased_range() {
+            let mut sampler = Sampler::new(LCG::seed_from_u64(6));
+            let mut observed = vec![0; 6];
+
+            for _ in 0..60_000 {
+                let roll = sampler.range(1, 7);
+                assert!((1..7).contains(&roll));
+                observed[roll as usize - 1] += 1;
+            }
+
+            let (_, p_value) = chi_square(&observed, &[1.0 / 6.0; 6]);
+            assert!(p_value > 0.01, "{:?}", observed);
+
+            // 3 * 2^62 would take half of the values `% n` maps to the lower third
+            let n = 3 << 62;
+            let low = (0..30_000).filter(|_| sampler.range(0, n) < n / 3).count();
+            assert!((9_000..11_000).contains(&low), "{}", low);
+        }
+
+        #[test]
+        fn continuous_moments() {
+            let mut sampler = Sampler::new(Xoshiro256::seed_from_u64(12));
+
+            let uniform = (0..100_000).map(|_| sampler.uniform()).collect::<Vec<_>>();
+            assert!(uniform.iter().all(|x| (0.0..1.0).contains(x)));
+            let (mean, variance) = moments(&uniform);
+            assert!((mean - 0.5).abs() < 0.01 && (variance - 1.0 / 12.0).abs() < 0.005);
+
+            let normal = (0..100_000)
+                .map(|_| sampler.normal(3.0, 2.0))
+                .collect::<Vec<_>>();
+            let (mean, variance) = moments(&normal);
+            assert!((mean - 3.0).abs() < 0.03 && (variance - 4.0).abs() < 0.1);
+
+            let exponential = (0..100_000)
+                .map(|_| sampler.exponential(4.0))
+                .collect::<Vec<_>>();
+            let (mean, variance) = moments(&exponential);
+            assert!((mean - 0.25).abs() < 0.005 && (variance - 0.0625).abs() < 0.005);
+        }
+
+        #[test]
+        fn poisson_moments() {
+            let mut sampler = Sampler::new(Xoshiro256::seed_from_u64(13));
+
+            // both the product of uniforms and the transformed rejection branch
+            for expected in [0.5, 4.0, 25.0, 1000.0] {
+                let samples = (0..50_000)
+                    .map(|_| sampler.poisson(expected) as f64)
+                    .collect::<Vec<_>>();
+                let (mean, variance) = moments(&samples);
+
+                assert!(
+                    (mean / expected - 1.0).abs() < 0.02,
+                    "{} {}",
+                    expected,
+                    mean
+                );
+                assert!(
+                    (variance / expected - 1.0).abs() < 0.05,
+                    "{} {}",
+                    expected,
+                    variance
+                );
+            }
+        }
+
+        #[test]
+        fn shuffle_and_reservoir() {
+            let mut sampler = Sampler::new(LCG::seed_from_u64(1));
+
+            let mut deck = (0..52).collect::<Vec<_>>();
+            sampler.shuffle(&mut deck);
+            assert_ne!(deck, (0..52).collect::<Vec<_>>());
+            deck.sort();
+            assert_eq!(deck, (0..52).collect::<Vec<_>>());
+
+            // every item of the stream ends up in the reservoir equally often
+            let mut observed = vec![0; 10];
+            for _ in 0..10_000 {
+                for item in sampler.reservoir(0..10, 3) {
+                    observed[item] += 1;
+                }
+            }
+
+            let (_, p_value) = chi_square(&observed, &[0.1; 10]);
+            assert!(p_value > 0.01, "{:?}", observed);
+            assert_eq!(sampler.reservoir(0..2, 3), vec![0, 1]);
+        }
+    }
+    mod generators {
+        use crate::bbs::{Bbs, BbsError};
+        use crate::generator::Generator;
+        use crate::lcg::LCG;
+        use crate::mt19937::MT19937;
+        use crate::pcg::PCG32;
+        use crate::stats::battery;
+        use crate::utils::is_prime;
+        use crate::xorshift::{Xorshift64, Xoshiro256};
+        use rand::{RngCore, SeedableRng};
+
+        #[test]
+        fn mt19937_reference() {
+            let mt = MT19937::new(5489);
+
+            assert_eq!(
+                mt.take(5).collect::<Vec<_>>(),
+                vec![3499211612, 581869302, 3890346734, 3586334585, 545404204]
+            );
+
+            // the 10000th output of the default seed, from the C++ standard
+            assert_eq!(MT19937::new(5489).nth(9999), Some(4123659995));
+        }
+
+        #[test]
+        fn xorshift_reference() {
+            let mut xorshift = Xorshift64::new(88172645463325252);
+            assert_eq!(xorshift.next_u64(), 8748534153485358512);
+
+            let xoshiro = Xoshiro256::new([1, 2, 3, 4]);
+            assert_eq!(
+                xoshiro.take(4).collect::<Vec<_>>(),
+                vec![11520, 0, 1509978240, 1215971899390074240]
+            );
+        }
+
+        #[test]
+        fn pcg32_reference() {
+            // pcg32-demo of the reference implementation
+            let mut pcg = PCG32::new(42, 54);
+            let nums = (0..6).map(|_| pcg.next_u32()).collect::<Vec<_>>();
+
+            assert_eq!(
+                nums,
+                vec![0xa15c02b7, 0x7b47f409, 0xba1d3330, 0x83d2f293, 0xbfa4784b, 0xcbed606e]
+            );
+        }
+
+        #[test]
+        fn bbs_bits() {
+            // n = 11 * 23 = 253, x_0 = 3^2: 81, 236, 36, 31
+            let bbs = Bbs::try_new(11, 23, 3).unwrap();
+            assert_eq!(bbs.take(4).collect::<Vec<_>>(), vec![1, 0, 0, 1]);
+
+            assert_eq!(
+                Bbs::try_new(13, 23, 3).unwrap_err(),
+                BbsError::NotBlumPrime(13)
+            );
+            assert_eq!(
+                Bbs::try_new(15, 23, 3).unwrap_err(),
+                BbsError::NotBlumPrime(15)
+            );
+            assert_eq!(Bbs::try_new(23, 23, 3).unwrap_err(), BbsError::EqualPrimes);
+            assert_eq!(
+                Bbs::try_new(11, 23, 46).unwrap_err(),
+                BbsError::SeedNotCoprime {
+                    seed: 46,
+                    modulus: 253
+                }
+            );
+            assert_eq!(
+                Bbs::try_new(4294967311, 4294967371, 2).unwrap_err(),
+                BbsError::ModulusOverflow
+            );
+
+            assert!(is_prime(4294967311) && is_prime(18446744073709551557));
+            assert!(!is_prime(3215031751) && !is_prime(1));
+        }
+
+        #[test]
+        fn common_interface() {
+            let generators: Vec<Box<dyn Generator>> = vec![
+                Box::new(LCG::seed_from_u64(7)),
+                Box::new(MT19937::new(7)),
+                Box::new(Xorshift64::new(7)),
+                Box::new(Xoshiro256::seed_from_u64(7)),
+                Box::new(PCG32::new(7, 7)),
+            ];
+
+            for generator in generators {
+                let modulus = generator.modulus();
+                let results = battery(generator, modulus, 100_000);
+
+                assert!(
+                    results.iter().all(|result| result.passed()),
+                    "{:?}",
+                    results
+                );
+            }
+        }
+    }
+    mod stats {
+        use crate::lcg::LCG;
+        use crate::stats::{battery, erfc, igamc};
+        use rand::SeedableRng;
+
+        #[test]
+        fn special_functions() {
+            // chi-square with one degree of freedom at its 95% quantile
+            assert!((igamc(0.5, 3.841_459 / 2.0) - 0.05).abs() < 1e-6);
+            // chi-square with ten degrees of freedom at 18.307
+            assert!((igamc(5.0, 18.307 / 2.0) - 0.05).abs() < 1e-4);
+            assert!((erfc(1.0) - 0.157_299_207_050_285_1).abs() < 1e-12);
+            assert!((erfc(-0.5) - 1.520_499_877_813_047).abs() < 1e-12);
+        }
+
+        #[test]
+        fn battery_good_generator() {
+            let lcg = LCG::seed_from_u64(2024);
+            let results = battery(lcg, Some(1 << 63), 200_000);
+
+            assert_eq!(results.len(), 7);
+            for result in results {
+                assert!(result.passed(), "{:?}", result);
+            }
+        }
+
+        #[test]
+        fn battery_bad_generator() {
+            // the lecture generator repeats every 4 values
+            let lcg = LCG::new(1 << 5, 7, 0, 1);
+            let results = battery(lcg, Some(1 << 5), 10_000);
+
+            assert!(results.iter().filter(|result| !result.passed()).count() >= 5);
+        }
+    }
+    mod nist {
+        use crate::lcg::LCG;
+        use crate::nist::*;
+        use rand::{RngCore, SeedableRng};
+
+        fn bits(input: &str) -> Vec<u8> {
+            input.bytes().map(|b| b - b'0').collect()
+        }
+
+        fn assert_p(actual: f64, expected: f64) {
+            assert!(
+                (actual - expected).abs() < 1e-6,
+                "p-value {} != {}",
+                actual,
+                expected
+            );
+        }
+
+        // examples from the test descriptions of SP 800-22 rev. 1a
+        #[test]
+        fn specification_examples() {
+            assert_p(monobit(&bits("1011010101")).p_value, 0.527089);
+            assert_p(block_frequency(&bits("0110011010"), 3).p_value, 0.801252);
+            assert_p(runs(&bits("1001101011")).p_value, 0.147232);
+            // the specification quotes 0.029523 with N1 = 4, but all five magnitudes
+            // of this input are below the threshold, N1 = 5
+            assert_p(dft(&bits("1001010011")).p_value, 0.468160);
+            assert_p(
+                non_overlapping_template(&bits("10100100101110010110"), &[0, 0, 1], 2).p_value,
+                0.344154,
+            );
+            // the worked example skips the c * sqrt(variance / K) correction of the
+            // reference implementation, so only the statistic is comparable
+            let universal = universal(&bits("01011010011101010111"), 2, 4);
+            assert!((universal.statistic - 1.1949875).abs() < 1e-6);
+            assert_p(
+                approximate_entropy(&bits("0100110101"), 3).p_value,
+                0.261961,
+            );
+
+            let serial = serial(&bits("0011011101"), 3);
+            assert_p(serial[0].p_value, 0.808792);
+            assert_p(serial[1].p_value, 0.670320);
+
+            assert_p(cumulative_sums(&bits("1011010111"))[0].p_value, 0.411658);
+
+            // state x = +1, the specification rounds 1/32 to 0.0312
+            assert!((random_excursions(&bits("0110110101"))[4].p_value - 0.502529).abs() < 1e-4);
+            assert_p(
+                random_excursions_variant(&bits("0110110101"))[9].p_value,
+                0.683091,
+            );
+
+            assert_eq!(berlekamp_massey(&bits("1101011110001")), 4);
+        }
+
+        // first n bits of the binary expansion of e, the leading "10" included, as in
+        // the data.e file of the reference implementation
+        fn e_bits(n: usize) -> Vec<u8> {
+            use num::{BigUint, One};
+
+            // sum of 1 / ((a + 1) ... k) for k in a + 1..=b as p / q, by binary splitting
+            fn split(a: u64, b: u64) -> (BigUint, BigUint) {
+                if b - a == 1 {
+                    return (BigUint::one(), BigUint::from(b));
+                }
+
+                let m = (a + b) / 2;
+                let (p1, q1) = split(a, m);
+                let (p2, q2) = split(m, b);
+
+                (p1 * &q2 + p2, q1 * q2)
+            }
+
+            // (n / 8)! is far above 2^n, the terms left out do not reach the last bit
+            let (p, q) = split(0, (n / 8) as u64);
+            let e = ((p + &q) << (n - 2)) / q;
+
+            e.to_str_radix(2).bytes().map(|b| b - b'0').collect()
+        }
+
+        // SP 800-22 section 2.8.8: 10^6 bits of e give the class counts 329, 164, 150, 111,
+        // 78 and 136. Its p-value 0.110434 comes from the uncorrected class probabilities,
+        // the corrected ones of revision 1a give 0.159027 for the same counts
+        #[test]
+        fn overlapping_template_on_e() {
+            let bits = e_bits(1_000_000);
+            assert_eq!(bits.len(), 1_000_000);
+            assert_eq!(&bits[..12], &[1, 0, 1, 0, 1, 1, 0, 1, 1, 1, 1, 1]);
+
+            let result = overlapping_template(&bits);
+            assert!((result.statistic - 7.949747).abs() < 1e-6);
+            assert_p(result.p_value, 0.159027);
+        }
+
+        #[test]
+        fn longest_run_example() {
+            let input = bits(
+                "11001100000101010110110001001100111000000000001001\
+                 00110101010001000100111101011010000000110101111100\
+                 1100111001101101100010110010",
+            );
+
+            // the class probabilities are rounded to four digits in the specification
+            assert!((longest_run(&input).p_value - 0.180609).abs() < 2e-5);
+        }
+
+        #[test]
+        fn suite_on_generators() {
+            let mut bytes = vec![0_u8; 1 << 14];
+            LCG::seed_from_u64(800).fill_bytes(&mut bytes);
+
+            let results = suite(&bits_from_bytes(&bytes));
+
+            assert_eq!(results.len(), 14);
+            assert!(
+                results.iter().all(|result| result.passed()),
+                "{:#?}",
+                results
+            );
+
+            let alternating = vec![0b0101_0101_u8; 1 << 14];
+            let results = suite(&bits_from_bytes(&alternating));
+
+            assert!(!results.iter().find(|r| r.name == "Runs").unwrap().passed());
+            assert!(!results
+                .iter()
+                .find(|r| r.name == "Approximate entropy")
+                .unwrap()
+                .passed());
+        }
+
+        // too short for any test, nothing is run rather than a test that divides by zero
+        #[test]
+        fn suite_on_short_input() {
+            for n in [0, 8, 24] {
+                assert!(suite(&vec![1; n]).is_empty());
+            }
+
+            let results = suite(&bits_from_bytes(&[0x5a; 16]));
+            assert_eq!(results.len(), 9);
+        }
+    }
+    mod output {
+        use crate::lcg::LCG;
+        use crate::output::{write_numbers, Format};
+
+        fn written(format: Format, modulus: Option<u64>, numbers: &[u64]) -> Vec<u8> {
+            let mut out = Vec::new();
+            write_numbers(&mut out, format, modulus, numbers.iter().copied()).unwrap();
+
+            out
+        }
+
+        #[test]
+        fn text_formats() {
+            let nums = [7, 17, 255];
+
+            assert_eq!(written(Format::Decimal, Some(32), &nums), b"7 17 255 \n");
+            assert_eq!(written(Format::Lines, Some(32), &nums), b"7\n17\n255\n");
+            assert_eq!(
+                written(Format::Hex, Some(1 << 32), &nums),
+                b"00000007\n00000011\n000000ff\n"
+            );
+            assert_eq!(written(Format::Hex, None, &[255]), b"00000000000000ff\n");
+            assert_eq!(
+                written(Format::Csv, Some(32), &nums),
+                b"index,value\n0,7\n1,17\n2,255\n"
+            );
+            assert!("xml".parse::<Format>().is_err());
+        }
+
+        #[test]
+        fn binary_format() {
+            assert_eq!(
+                written(Format::Binary, Some(1 << 31), &[0x41a7, 0x10d63af1]),
+                vec![0xa7, 0x41, 0, 0, 0xf1, 0x3a, 0xd6, 0x10]
+            );
+
+            // a million values stream through without being collected
+            let mut out = Vec::new();
+            let lcg = LCG::new(1 << 63, 6364136223846793005, 1, 0);
+            write_numbers(&mut out, Format::Binary, Some(1 << 63), lcg.take(1_000_000)).unwrap();
+
+            assert_eq!(out.len(), 8_000_000);
+        }
+    }
+    mod distinct {
+        use crate::distinct::{Distinct, HyperLogLog};
+        use crate::lcg::LCG;
+        use crate::utils::unique;
+
+        #[test]
+        fn exact_for_small_modulus() {
+            let modulus = (1 << 11) - 1;
+            let lcg = LCG::new(modulus, 3_u64.pow(5), 1, 4);
+            let nums = lcg.clone().take(100_000).collect::<Vec<u64>>();
+
+            let mut distinct = Distinct::new(Some(modulus));
+            distinct.extend(lcg.take(100_000));
+
+            assert!(distinct.is_exact());
+            assert_eq!(distinct.count(), unique(&nums) as u64);
+            assert_eq!(distinct.count(), 88);
+        }
+
+        #[test]
+        fn hyperloglog_estimate() {
+            assert!(!Distinct::new(None).is_exact());
+            assert!(!Distinct::new(Some(1 << 31)).is_exact());
+            assert!(!Distinct::new(Some(1 << 40)).is_exact());
+            assert_eq!(HyperLogLog::new().count(), 0);
+
+            // repeats do not change the estimate
+            let mut hll = HyperLogLog::new();
+            for value in (0..1000).chain(0..1000) {
+                hll.insert(value);
+            }
+            assert!(hll.count().abs_diff(1000) <= 10);
+
+            // a full period LCG repeats nothing, 2^20 values within 3%
+            let lcg = LCG::new(1 << 63, 6364136223846793005, 1442695040888963407, 1);
+            let mut distinct = Distinct::new(Some(1 << 63));
+            distinct.extend(lcg.take(1 << 20));
+            let estimate = distinct.count();
+
+            assert!(
+                estimate.abs_diff(1 << 20) < (1 << 20) * 3 / 100,
+                "{}",
+                estimate
+            );
+        }
+    }
+    mod spectral {
+        use crate::spectral::{rank, spectral_test};
+
+        // shortest nonzero x with x_1 + a x_2 + ... + a^(t-1) x_t = 0 mod m, by brute force.
+        // nu_t <= nu_2 <= sqrt(4m / 3), so no coordinate of the answer exceeds that
+        fn brute_force(modulus: i64, multiplier: i64, dimension: u32) -> u128 {
+            let range = (4.0 * modulus as f64 / 3.0).sqrt().ceil() as i64;
+            let total = (2 * range + 1).pow(dimension);
+            let mut best = u128::MAX;
+
+            for index in 0..total {
+                let mut rest = index;
+                let mut residue = 0;
+                let mut norm = 0;
+                let mut power = 1;
+
+                for _ in 0..dimension {
+                    let x = rest % (2 * range + 1) - range;
+                    rest /= 2 * range + 1;
+
+                    residue = (residue + x * power).rem_euclid(modulus);
+                    norm += (x * x) as u128;
+                    power = power * multiplier % modulus;
+                }
+
+                if residue == 0 && norm > 0 {
+                    best = best.min(norm);
+                }
+            }
+
+            best
+        }
+
+        #[test]
+        fn randu() {
+            // Knuth, TAOCP 3.3.4, table 1: RANDU falls into 15 planes in three dimensions.
+            // a = 3 mod 8 with m = 2^31 gives the lattice of the odd states, m / 4
+            let results = spectral_test(1 << 29, 65539, 6);
+            let nu_squared = results.iter().map(|r| r.nu_squared).collect::<Vec<_>>();
+
+            assert_eq!(nu_squared, vec![536936458, 118, 116, 116, 116]);
+            assert!(results[1].merit < 0.02);
+        }
+
+        #[test]
+        fn small_modulus() {
+            for multiplier in [3, 5, 13, 21, 37] {
+                let results = spectral_test(64, multiplier, 4);
+
+                for result in &results {
+                    assert_eq!(
+                        result.nu_squared,
+                        brute_force(64, multiplier as i64, result.dimension as u32),
+                        "a = {}, t = {}",
+                        multiplier,
+                        result.dimension
+                    );
+                    assert!(result.merit <= 1.0 + 1e-9);
+                }
+            }
+        }
+
+        #[test]
+        fn rank_multipliers() {
+            // minstd and its successor against RANDU modulo 2^31 - 1
+            let ranked = rank((1 << 31) - 1, &[65539, 16807, 48271], 8);
+            let order = ranked.iter().map(|(a, _)| *a).collect::<Vec<_>>();
+
+            assert_eq!(order[2], 65539);
+            assert_eq!(ranked[0].1.len(), 7);
+        }
+    }
+    mod plot {
+        use crate::lcg::LCG;
+        use crate::plot::{histogram, pairs, triples};
+
+        fn randu(count: usize) -> Vec<f64> {
+            let lcg = LCG::new(1 << 31, 65539, 0, 1);
+
+            lcg.take(count)
+                .map(|x| x as f64 / (1_u64 << 31) as f64)
+                .collect()
+        }
+
+        #[test]
+        fn svg_and_ppm() {
+            let canvas = pairs(&randu(101), 600);
+            let svg = canvas.to_svg();
+
+            assert!(svg.starts_with("<svg"));
+            assert!(svg.ends_with("</svg>\n"));
+            assert_eq!(svg.matches("<circle").count(), 100);
+            assert_eq!(svg.matches("<line").count(), 4);
+
+            let header = b"P6\n600 600\n255\n";
+            let ppm = canvas.to_ppm();
+
+            assert!(ppm.starts_with(header));
+            assert_eq!(ppm.len(), header.len() + 600 * 600 * 3);
+            assert!(ppm[header.len()..].contains(&0));
+        }
+
+        #[test]
+        fn randu_planes() {
+            let values = randu(1002);
+            let canvas = triples(&values, 400, 32.8, 10.0);
 
-            let mut nums = Vec::<u64>::with_capacity(1000);
-            for _ in 0..1000 {
-                let num = lcg.next().expect("Failed generate next number!");
+            // twelve cube edges, one point per overlapping triple
+            assert_eq!(canvas.to_svg().matches("<line").count(), 12);
+            assert_eq!(canvas.to_svg().matches("<circle").count(), 1000);
 
-                nums.push(num);
+            // every triple lies on one of the planes 9x - 6y + z = k
+            for triple in values.windows(3) {
+                let k = 9.0 * triple[0] - 6.0 * triple[1] + triple[2];
+                assert!((k - k.round()).abs() < 1e-6);
             }
+        }
 
-            println!("{:?}", nums);
-            println!("{}", unique(&nums));
+        #[test]
+        fn histogram_bins() {
+            let values = [0.05, 0.1, 0.3, 0.35, 0.4, 0.99];
+            let output = histogram(&values, 4, 10);
+            let lines: Vec<&str> = output.lines().collect();
 
-            assert_eq!(nums.len(), 1000);
-            assert!(unique(&nums) >= 50);
+            assert_eq!(lines.len(), 4);
+            assert_eq!(lines[0], "[0.000, 0.250) ######     2");
+            assert_eq!(lines[1], "[0.250, 0.500) ########## 3");
+            assert_eq!(lines[2], "[0.500, 0.750)            0");
+            assert_eq!(lines[3], "[0.750, 1.000) ###        1");
         }
     }
     mod md5 {
-        use crate::md5::MD5;
+        use crate::md5::{Digest, Md5, MD5};
+        use std::io::Read;
 
         #[test]
         fn empty_string() {
@@ -108,15 +1156,700 @@ mod core {
                 "57EDF4A22BE3C955AC49DA2E2107B67A"
             );
         }
+
+        #[test]
+        fn binary_input() {
+            let bytes = (0..=255).collect::<Vec<u8>>();
+
+            assert_eq!(
+                MD5::from_bytes(&[0xFF, 0xFE, 0x00, 0x80]),
+                "BEFDD6D5DD41EC321AB57139806EDBB1"
+            );
+            assert_eq!(MD5::from_bytes(&bytes), "E2C865DB4162BED963BFAA9EF6AC18F0");
+            assert_eq!(
+                MD5::from_reader(&bytes[..]).unwrap(),
+                MD5::from_bytes(&bytes)
+            );
+            assert_eq!(MD5::from_bytes(b"abc"), MD5::from("abc"));
+        }
+
+        #[test]
+        fn digest_encodings() {
+            let empty = MD5::digest(b"");
+
+            assert_eq!(empty.to_hex(), "d41d8cd98f00b204e9800998ecf8427e");
+            assert_eq!(empty.to_upper_hex(), "D41D8CD98F00B204E9800998ECF8427E");
+            assert_eq!(empty.to_string(), MD5::from(""));
+            assert_eq!(empty.to_base64(), "1B2M2Y8AsgTpgAmY7PhCfg==");
+            assert_eq!(MD5::digest(b"abc").to_base64(), "kAFQmDzST7DWlj99KOF/cg==");
+            assert_eq!(empty.as_bytes()[..4], [0xD4, 0x1D, 0x8C, 0xD9]);
+            assert_eq!(<[u8; 16]>::from(empty), *empty.as_bytes());
+        }
+
+        #[test]
+        fn digest_parse_and_compare() {
+            let abc = MD5::digest(b"abc");
+
+            assert_eq!(
+                "900150983cd24fb0d6963f7d28e17f72".parse::<Digest>(),
+                Ok(abc)
+            );
+            assert_eq!(abc.to_string().parse::<Digest>(), Ok(abc));
+            assert_ne!(abc, MD5::digest(b"abd"));
+
+            assert!("900150983cd24fb0".parse::<Digest>().is_err());
+            assert!("900150983cd24fb0d6963f7d28e17fzz"
+                .parse::<Digest>()
+                .is_err());
+            assert!("900150983cd24fb0d6963f7d28e17f7é"
+                .parse::<Digest>()
+                .is_err());
+        }
+
+        #[test]
+        fn digest_traits() {
+            use digest::{Digest as _, FixedOutputReset, Reset};
+
+            assert_eq!(<Md5 as digest::Digest>::output_size(), 16);
+            assert_eq!(
+                <Md5 as digest::Digest>::digest(b"message digest")[..],
+                MD5::digest(b"message digest").as_bytes()[..]
+            );
+
+            let mut md5 = <Md5 as digest::Digest>::new().chain_update(b"a");
+            digest::Update::update(&mut md5, b"bc");
+            assert_eq!(
+                md5.finalize_fixed_reset()[..],
+                "900150983cd24fb0d6963f7d28e17f72"
+                    .parse::<Digest>()
+                    .unwrap()
+                    .0
+            );
+
+            // finalizing reset the state, so this is the hash of the empty string
+            assert_eq!(
+                digest::FixedOutput::finalize_fixed(md5.clone())[..],
+                MD5::digest(b"").0
+            );
+
+            digest::Update::update(&mut md5, b"discarded");
+            Reset::reset(&mut md5);
+            assert_eq!(
+                digest::FixedOutput::finalize_fixed(md5)[..],
+                MD5::digest(b"").0
+            );
+        }
+
+        // a 1024 bit key keeps the test fast, it is no weaker than MD5 anyway
+        #[test]
+        #[allow(deprecated)]
+        fn dsa_with_md5() {
+            use crate::lcg::{Insecure, LCG};
+            use digest::Digest as _;
+            use dsa::signature::{DigestVerifier, RandomizedDigestSigner};
+            use dsa::{Components, KeySize, SigningKey};
+            use rand::SeedableRng;
+
+            let mut rng = Insecure(LCG::seed_from_u64(19));
+            let components = Components::generate(&mut rng, KeySize::DSA_1024_160);
+            let signing_key = SigningKey::generate(&mut rng, components);
+            let verifying_key = signing_key.verifying_key();
+
+            let signature =
+                signing_key.sign_digest_with_rng(&mut rng, Md5::new().chain_update(b"message"));
+
+            assert!(verifying_key
+                .verify_digest(Md5::new().chain_update(b"message"), &signature)
+                .is_ok());
+            assert!(verifying_key
+                .verify_digest(Md5::new().chain_update(b"massage"), &signature)
+                .is_err());
+        }
+
+        #[test]
+        fn incremental_updates() {
+            let input = (0..1000).map(|i| (i % 251) as u8).collect::<Vec<u8>>();
+
+            // lengths around the padding and block boundaries, fed in uneven pieces
+            for length in [0, 1, 55, 56, 63, 64, 65, 119, 120, 128, 1000] {
+                let mut whole = Md5::new();
+                whole.update(&input[..length]);
+
+                let mut pieces = Md5::new();
+                for piece in input[..length].chunks(7) {
+                    pieces.update(piece);
+                }
+
+                let expected = MD5::from_reader(&input[..length]).unwrap();
+
+                assert_eq!(whole.finalize().to_upper_hex(), expected);
+                assert_eq!(pieces.finalize().to_upper_hex(), expected);
+            }
+        }
+
+        #[test]
+        fn million_letters() {
+            // RFC 1321 test suite extension, read through io::copy in chunks
+            let reader = std::io::repeat(b'a').take(1_000_000);
+
+            assert_eq!(
+                MD5::from_reader(reader).unwrap(),
+                "7707D6AE4E027C70EEA2A935C2296F21"
+            );
+        }
+    }
+    mod hmac {
+        use crate::hmac::{hmac, Hmac};
+        use crate::md5::Md5;
+        use crate::sha1::Sha1;
+        use crate::utils::from_hex;
+
+        // RFC 2202 test cases: key, data, HMAC-MD5, HMAC-SHA1
+        fn cases() -> Vec<(Vec<u8>, Vec<u8>, &'static str, &'static str)> {
+            vec![
+                (
+                    vec![0x0b; 16],
+                    b"Hi There".to_vec(),
+                    "9294727a3638bb1c13f48ef8158bfc9d",
+                    "b617318655057264e28bc0b6fb378c8ef146be00",
+                ),
+                (
+                    b"Jefe".to_vec(),
+                    b"what do ya want for nothing?".to_vec(),
+                    "750c783e6ab0b503eaa86e310a5db738",
+                    "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79",
+                ),
+                (
+                    vec![0xaa; 16],
+                    vec![0xdd; 50],
+                    "56be34521d144c88dbb8c733f0e8b3f6",
+                    "125d7342b9ac11cd91a39af48aa17b4f63f175d3",
+                ),
+                (
+                    (1..=25).collect(),
+                    vec![0xcd; 50],
+                    "697eaf0aca3a3aea3a75164746ffaa79",
+                    "4c9007f4026250c6bc8414f9bf50c86c2d7235da",
+                ),
+                (
+                    vec![0x0c; 16],
+                    b"Test With Truncation".to_vec(),
+                    "56461ef2342edc00f9bab995690efd4c",
+                    "4c1a03424b55e07fe7f27be1d58bb9324a9a5a04",
+                ),
+                (
+                    vec![0xaa; 80],
+                    b"Test Using Larger Than Block-Size Key - Hash Key First".to_vec(),
+                    "6b1ab7fe4bd7bf8f0b62e6ce61b9d0cd",
+                    "aa4ae5e15272d00e95705637ce8a3b55ed402112",
+                ),
+                (
+                    vec![0xaa; 80],
+                    b"Test Using Larger Than Block-Size Key and Larger Than One Block-Size Data"
+                        .to_vec(),
+                    "6f630fad67cda0ee1fb1f562db3aa53e",
+                    "e8e99d0f45237d786d6bbaa7965c7808bbff1a91",
+                ),
+            ]
+        }
+
+        #[test]
+        fn rfc2202_md5() {
+            for (key, data, expected, _) in cases() {
+                assert_eq!(
+                    hmac::<Md5>(&key, &data)[..],
+                    from_hex(expected).unwrap()[..]
+                );
+            }
+        }
+
+        #[test]
+        fn rfc2202_sha1() {
+            for (mut key, data, _, expected) in cases() {
+                // cases 1, 3 and 5 use a 20 byte key for SHA1, the block is repeated
+                if key.len() == 16 {
+                    key.resize(20, key[0]);
+                }
+
+                assert_eq!(
+                    hmac::<Sha1>(&key, &data)[..],
+                    from_hex(expected).unwrap()[..]
+                );
+            }
+        }
+
+        #[test]
+        fn streaming_and_verify() {
+            let mut pieces = Hmac::<Md5>::new(b"Jefe");
+            for piece in [&b"what do ya "[..], b"want ", b"for nothing?"] {
+                pieces.update(piece);
+            }
+
+            let tag = from_hex("750c783e6ab0b503eaa86e310a5db738").unwrap();
+            assert!(pieces.clone().verify(&tag));
+
+            let mut wrong = tag.clone();
+            wrong[15] ^= 1;
+            assert!(!pieces.clone().verify(&wrong));
+            assert!(!pieces.verify(&tag[..15]));
+        }
+
+        #[test]
+        fn from_hex_digits_only() {
+            assert_eq!(from_hex("00fF"), Some(vec![0x00, 0xff]));
+            assert_eq!(from_hex("+f"), None);
+            assert_eq!(from_hex("-1"), None);
+            assert_eq!(from_hex("abc"), None);
+            assert!("+f000000000000000000000000000000"
+                .parse::<crate::md5::Digest>()
+                .is_err());
+        }
+    }
+    mod multibuffer {
+        use crate::md5::{MD5, TABLE};
+        use crate::multibuffer::{digest_many, Engine};
+
+        #[test]
+        fn table_is_rfc1321() {
+            for (i, value) in TABLE.iter().enumerate() {
+                let sine = ((i + 1) as f64).sin().abs();
+                assert_eq!(*value, (4294967296.0 * sine) as u32, "T[{}]", i + 1);
+            }
+        }
+
+        #[test]
+        fn engines_match_md5() {
+            // every padding case, and more messages than lanes so lanes are refilled
+            let messages = (0..300)
+                .map(|length| (0..length).map(|i| (i * 7 + length) as u8).collect())
+                .collect::<Vec<Vec<u8>>>();
+            let inputs = messages.iter().map(Vec::as_slice).collect::<Vec<_>>();
+
+            let expected = inputs
+                .iter()
+                .map(|input| MD5::digest(input))
+                .collect::<Vec<_>>();
+
+            for engine in Engine::available() {
+                assert_eq!(engine.digest_many(&inputs), expected, "{}", engine);
+
+                let reversed = inputs.iter().rev().copied().collect::<Vec<_>>();
+                let digests = engine.digest_many(&reversed);
+                assert!(digests.iter().rev().eq(&expected), "{}", engine);
+            }
+        }
+
+        #[test]
+        fn fewer_messages_than_lanes() {
+            assert!(digest_many(&[]).is_empty());
+            assert_eq!(
+                digest_many(&[b"abc"])[0].to_hex(),
+                "900150983cd24fb0d6963f7d28e17f72"
+            );
+            assert!(Engine::available().contains(&Engine::Portable));
+        }
+    }
+    mod extension {
+        use crate::extension::forge;
+        use crate::md5::{padding, Md5, MD5};
+
+        #[test]
+        fn glue_padding() {
+            for length in [0, 1, 55, 56, 63, 64, 119, 1000] {
+                let padded = length + padding(length).len() as u64;
+
+                assert_eq!(padded % 64, 0, "{}", length);
+                assert!(padded - length >= 9 && padded - length <= 72, "{}", length);
+            }
+
+            assert_eq!(padding(3)[..2], [0x80, 0]);
+            assert_eq!(padding(3)[53..], 24_u64.to_le_bytes());
+        }
+
+        #[test]
+        fn resume_from_digest() {
+            let block = [b'a'; 128];
+
+            let mut resumed = Md5::resume(MD5::digest(&block[..64]), 128);
+            resumed.update(b"tail");
+
+            let glued = [&block[..64], &padding(64)[..], b"tail"].concat();
+            assert_eq!(resumed.finalize(), MD5::digest(&glued));
+        }
+
+        #[test]
+        #[should_panic]
+        fn resume_off_block() {
+            Md5::resume(MD5::digest(b""), 100);
+        }
+
+        #[test]
+        fn forged_message_verifies() {
+            let secret = b"sixteen byte key";
+            let message = b"count=10&lat=37.351&user_id=1&long=-119.827&waffle=eggo";
+
+            for secret_length in [0, 5, 16] {
+                let secret = &secret[..secret_length];
+                let digest = MD5::digest(&[secret, &message[..]].concat());
+
+                let forgery = forge(digest, secret_length, message, b"&waffle=liege");
+
+                assert!(forgery.message.starts_with(message));
+                assert!(forgery.message.ends_with(b"&waffle=liege"));
+                assert_eq!(
+                    forgery.digest,
+                    MD5::digest(&[secret, &forgery.message[..]].concat())
+                );
+            }
+        }
+    }
+    mod sha2 {
+        use crate::hmac::hmac;
+        use crate::sha2::{Digest, Sha224, Sha256, Sha384, Sha512, Sha512_256};
+        use crate::utils::from_hex;
+
+        const ONE_BLOCK: &[u8] = b"abc";
+        const TWO_BLOCKS_256: &[u8] = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+        const TWO_BLOCKS_512: &[u8] = b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+
+        // FIPS 180-4 examples: one block, then the two block message of each word size
+        #[test]
+        fn fips_180_4() {
+            assert_eq!(
+                Sha224::digest(ONE_BLOCK).to_hex(),
+                "23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7"
+            );
+            assert_eq!(
+                Sha224::digest(TWO_BLOCKS_256).to_hex(),
+                "75388b16512776cc5dba5da1fd890150b0c6455cb4f58b1952522525"
+            );
+            assert_eq!(
+                Sha256::digest(ONE_BLOCK).to_hex(),
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+            );
+            assert_eq!(
+                Sha256::digest(TWO_BLOCKS_256).to_hex(),
+                "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+            );
+            assert_eq!(
+                Sha384::digest(ONE_BLOCK).to_hex(),
+                "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7"
+            );
+            assert_eq!(
+                Sha384::digest(TWO_BLOCKS_512).to_hex(),
+                "09330c33f71147e83d192fc782cd1b4753111b173b3b05d22fa08086e3b0f712fcc7c71a557e2db966c3e9fa91746039"
+            );
+            assert_eq!(
+                Sha512::digest(ONE_BLOCK).to_hex(),
+                "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+            );
+            assert_eq!(
+                Sha512::digest(TWO_BLOCKS_512).to_hex(),
+                "8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909"
+            );
+            assert_eq!(
+                Sha512_256::digest(ONE_BLOCK).to_hex(),
+                "53048e2681941ef99b2e29b76b4c7dabe4c2d0c634fc6d46e0e2f13107e7af23"
+            );
+            assert_eq!(
+                Sha512_256::digest(TWO_BLOCKS_512).to_hex(),
+                "3928e184fb8690f840da3988121d31be65cb9d3ef83ee6146feac861e19b563a"
+            );
+        }
+
+        #[test]
+        fn million_a() {
+            let input = vec![b'a'; 1_000_000];
+
+            assert_eq!(
+                Sha256::digest(&input).to_hex(),
+                "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+            );
+            assert_eq!(
+                Sha512::digest(&input).to_hex(),
+                "e718483d0ce769644e2e42c7bc15b4638e1f98b13b2044285632a803afa973ebde0ff244877ea60a4cb0432ce577c31beb009c5c2c49aa2e4eadb217ad8cc09b"
+            );
+        }
+
+        #[test]
+        fn streaming_and_formats() {
+            let input = (0..1000).map(|i| i as u8).collect::<Vec<u8>>();
+
+            // pieces that straddle both block sizes
+            let mut sha256 = Sha256::new();
+            let mut sha384 = Sha384::new();
+            for piece in input.chunks(37) {
+                sha256.update(piece);
+                sha384.update(piece);
+            }
+            assert_eq!(sha256.finalize(), Sha256::digest(&input));
+            assert_eq!(sha384.finalize(), Sha384::digest(&input));
+
+            let reader = Sha512::digest_reader(&input[..]).unwrap();
+            assert_eq!(reader, Sha512::digest(&input));
+
+            let digest = Sha224::digest(b"abc");
+            assert_eq!(digest.to_string(), digest.to_upper_hex());
+            assert_eq!(digest.to_upper_hex().parse::<Digest<28>>(), Ok(digest));
+            assert!(digest.to_hex().parse::<Digest<32>>().is_err());
+            assert_eq!(
+                Sha256::digest(b"").to_base64(),
+                "47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU="
+            );
+        }
+
+        // RFC 4231 test case 2 through the RustCrypto traits
+        #[test]
+        fn hmac_sha256() {
+            let expected =
+                from_hex("5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+
+            assert_eq!(
+                hmac::<Sha256>(b"Jefe", b"what do ya want for nothing?")[..],
+                expected.unwrap()[..]
+            );
+        }
+    }
+    mod sha1 {
+        use crate::sha1::{expand, step, unstep, Kind, Sha1, DISTURBANCES};
+        use crate::utils::from_hex;
+
+        // first 192 bytes, three blocks, the SHAttered PDFs share, then their 128 bytes
+        // that differ: a near-collision block and the block that completes the collision
+        const SHATTERED_PREFIX: &str = concat!(
+            "255044462d312e330a25e2e3cfd30a0a0a312030206f626a0a3c3c2f57696474",
+            "682032203020522f4865696768742033203020522f547970652034203020522f",
+            "537562747970652035203020522f46696c7465722036203020522f436f6c6f72",
+            "53706163652037203020522f4c656e6774682038203020522f42697473506572",
+            "436f6d706f6e656e7420383e3e0a73747265616d0affd8fffe00245348412d31",
+            "20697320646561642121212121852fec092339759c39b1a1c63c4c97e1fffe01",
+        );
+        const SHATTERED_1: &str = concat!(
+            "7346dc9166b67e118f029ab621b2560ff9ca67cca8c7f85ba84c79030c2b3de2",
+            "18f86db3a90901d5df45c14f26fedfb3dc38e96ac22fe7bd728f0e45bce046d2",
+            "3c570feb141398bb552ef5a0a82be331fea48037b8b5d71f0e332edf93ac3500",
+            "eb4ddc0decc1a864790c782c76215660dd309791d06bd0af3f98cda4bc4629b1",
+        );
+        const SHATTERED_2: &str = concat!(
+            "7f46dc93a6b67e013b029aaa1db2560b45ca67d688c7f84b8c4c791fe02b3df6",
+            "14f86db1690901c56b45c1530afedfb76038e972722fe7ad728f0e4904e046c2",
+            "30570fe9d41398abe12ef5bc942be33542a4802d98b5d70f2a332ec37fac3514",
+            "e74ddc0f2cc1a874cd0c78305a21566461309789606bd0bf3f98cda8044629a1",
+        );
+
+        // FIPS 180-4 examples and the million 'a' of the old test suites
+        #[test]
+        fn fips_180_4() {
+            assert_eq!(
+                Sha1::digest(b"abc").to_hex(),
+                "a9993e364706816aba3e25717850c26c9cd0d89d"
+            );
+            assert_eq!(
+                Sha1::digest(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq").to_hex(),
+                "84983e441c3bd26ebaae4aa1f95129e5e54670f1"
+            );
+            assert_eq!(
+                Sha1::digest(&vec![b'a'; 1_000_000]).to_hex(),
+                "34aa973cd4c4daa4f61eeb2bdbad27316534016f"
+            );
+        }
+
+        #[test]
+        fn streaming() {
+            let input = (0..1000).map(|i| i as u8).collect::<Vec<u8>>();
+
+            let mut sha1 = Sha1::new();
+            for piece in input.chunks(37) {
+                sha1.update(piece);
+            }
+
+            assert_eq!(sha1.finalize(), Sha1::digest(&input));
+            assert_eq!(
+                Sha1::digest_reader(&input[..]).unwrap(),
+                Sha1::digest(&input)
+            );
+        }
+
+        #[test]
+        fn unstep_inverts_step() {
+            let w = expand(&[0x5a; 64]);
+            let initial = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+            let mut state = initial;
+            for (t, &word) in w.iter().enumerate() {
+                step(&mut state, t, word);
+            }
+            for (t, &word) in w.iter().enumerate().rev() {
+                unstep(&mut state, t, word);
+            }
+
+            assert_eq!(state, initial);
+        }
+
+        // every mask is a valid message difference, I(43,0) starts as in sha1dc's table and
+        // vectors from K = 50 on are recompressed from step 65 as there
+        #[test]
+        fn disturbance_vectors() {
+            assert_eq!(DISTURBANCES.len(), 32);
+            for disturbance in DISTURBANCES.iter() {
+                let step = if disturbance.k < 50 { 58 } else { 65 };
+                assert_eq!(disturbance.step, step, "{}", disturbance);
+
+                let mask = disturbance.mask;
+                for t in 16..80 {
+                    assert_eq!(
+                        mask[t],
+                        (mask[t - 3] ^ mask[t - 8] ^ mask[t - 14] ^ mask[t - 16]).rotate_left(1)
+                    );
+                }
+            }
+
+            let i43 = DISTURBANCES
+                .iter()
+                .find(|d| d.kind == Kind::I && d.k == 43 && d.b == 0)
+                .unwrap();
+            assert_eq!(i43.mask[..3], [0x08000000, 0x9800000c, 0xd8000010]);
+        }
+
+        #[test]
+        fn no_false_positives() {
+            let input = (0..100_000)
+                .map(|i| (i * 7 + i / 13) as u8)
+                .collect::<Vec<u8>>();
+
+            let mut sha1 = Sha1::with_collision_detection();
+            sha1.update(&input);
+
+            assert_eq!(sha1.finalize_checked().unwrap(), Sha1::digest(&input));
+        }
+
+        #[test]
+        fn shattered() {
+            let prefix = from_hex(SHATTERED_PREFIX).unwrap();
+
+            for blocks in [SHATTERED_1, SHATTERED_2] {
+                let input = [prefix.clone(), from_hex(blocks).unwrap()].concat();
+
+                assert_eq!(
+                    Sha1::digest(&input).to_hex(),
+                    "f92d74e3874587aaf443d1db961d4e26dde13e9c"
+                );
+
+                // the shared prefix and the near-collision block alone raise nothing
+                for clean in [&input[..192], &input[..256]] {
+                    let mut sha1 = Sha1::with_collision_detection();
+                    sha1.update(clean);
+                    assert!(sha1.finalize_checked().is_ok());
+                }
+
+                // detection fires in block 4 that completes the collision, however much
+                // of the file follows it
+                for trailer in [0, 64, 1000] {
+                    let mut sha1 = Sha1::with_collision_detection();
+                    sha1.update(&input);
+                    sha1.update(&vec![0x0a; trailer]);
+                    let collision = sha1.finalize_checked().unwrap_err();
+
+                    assert_eq!(collision.block, 4);
+                    assert_eq!(collision.disturbance.to_string(), "II(52,0)");
+                }
+            }
+        }
+    }
+    mod checksum {
+        use crate::checksum::{Algorithm, Entry, Report, Status};
+        use crate::md5::MD5;
+
+        #[test]
+        fn parse_entries() {
+            let text =
+                Entry::parse("900150983cd24fb0d6963f7d28e17f72  a b.txt", Algorithm::Md5).unwrap();
+            assert_eq!(text.digest, MD5::digest(b"abc").as_bytes());
+            assert_eq!(text.file, "a b.txt");
+
+            // binary mode marker and uppercase hex
+            let binary =
+                Entry::parse("900150983CD24FB0D6963F7D28E17F72 *a b.txt", Algorithm::Md5).unwrap();
+            assert_eq!(binary, text);
+
+            for line in [
+                "900150983cd24fb0d6963f7d28e17f72",
+                "900150983cd24fb0d6963f7d28e17f72  ",
+                "900150983cd24fb0d6963f7d28e17f72 a.txt",
+                "900150983cd24fb0d6963f7d28e17f7  a.txt",
+                "900150983cd24fb0d6963f7d28e17f7g  a.txt",
+                "a.txt: OK",
+            ] {
+                assert_eq!(Entry::parse(line, Algorithm::Md5), None, "{}", line);
+            }
+        }
+
+        #[test]
+        fn md5sum_line() {
+            let entry = Entry {
+                digest: Algorithm::Md5.digest(b""),
+                file: String::from("-"),
+            };
+
+            assert_eq!(entry.to_string(), "d41d8cd98f00b204e9800998ecf8427e  -");
+            assert_eq!(
+                Entry::parse(&entry.to_string(), Algorithm::Md5),
+                Some(entry.clone())
+            );
+            assert_eq!(Entry::parse(&entry.to_string(), Algorithm::Sha256), None);
+        }
+
+        #[test]
+        fn algorithm_names() {
+            assert_eq!(Algorithm::Sha512_256.to_string(), "SHA512/256");
+            assert_eq!(Algorithm::Sha512_256.name(), "sha512_256");
+
+            for algorithm in [Algorithm::Md5, Algorithm::Sha1, Algorithm::Sha512_256] {
+                assert_eq!(algorithm.name().parse(), Ok(algorithm));
+            }
+        }
+
+        #[test]
+        fn report_warnings() {
+            let mut report = Report::default();
+            assert!(!report.passed());
+            assert_eq!(
+                report.warnings(Algorithm::Md5),
+                ["no properly formatted MD5 checksum lines found"]
+            );
+            assert_eq!(
+                report.warnings(Algorithm::Sha256),
+                ["no properly formatted SHA256 checksum lines found"]
+            );
+
+            report.record(Status::Ok);
+            assert!(report.passed());
+            assert!(report.warnings(Algorithm::Md5).is_empty());
+
+            report.malformed = 2;
+            report.record(Status::Missing);
+            report.record(Status::Failed);
+            report.record(Status::Failed);
+            assert!(!report.passed());
+            assert_eq!(report.checked(), 4);
+            assert_eq!(
+                report.warnings(Algorithm::Md5),
+                [
+                    "WARNING: 2 lines are improperly formatted",
+                    "WARNING: 1 listed file could not be read",
+                    "WARNING: 2 computed checksums did NOT match",
+                ]
+            );
+        }
     }
     mod rc5 {
-        use crate::rc5::Flags::{CBC, CBC_MD5, ECB};
+        use crate::rc5::Flags::{Cbc, CbcMd5, Ecb};
         use crate::rc5::RC5;
 
         // RC5-32/12/16
         #[test]
         fn simple() {
-            let rc = RC5::<u32>::new(12, 16, ECB);
+            let rc = RC5::<u32>::new(12, 16, Ecb);
 
             let key = &[
                 0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D,
@@ -134,7 +1867,7 @@ mod core {
         // RC5-32/12/16
         #[test]
         fn simple_cbc() {
-            let mut rc = RC5::<u32>::new(12, 16, CBC);
+            let mut rc = RC5::<u32>::new(12, 16, Cbc);
 
             let key = &[
                 0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D,
@@ -152,7 +1885,7 @@ mod core {
         // RC5-32/12/16
         #[test]
         fn long_input_cbc() {
-            let mut rc = RC5::<u32>::new(12, 16, CBC);
+            let mut rc = RC5::<u32>::new(12, 16, Cbc);
 
             let key = &[
                 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
@@ -171,7 +1904,7 @@ mod core {
         // RC5-32/12/16
         #[test]
         fn simple_cbc_md5() {
-            let mut rc = RC5::<u32>::new(12, 16, CBC_MD5);
+            let mut rc = RC5::<u32>::new(12, 16, CbcMd5);
 
             let key = &[
                 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
@@ -186,5 +1919,17 @@ mod core {
 
             assert_eq!(plain, pt);
         }
+        // keys that are not UTF-8 are hashed as they are, not as replacement characters
+        #[test]
+        fn binary_key_md5() {
+            let rc = RC5::<u32>::new(12, 16, CbcMd5);
+            let pt = &[0x00u8, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77];
+
+            let first = rc.encrypt(pt, &[0xFF, 0x00]);
+            let second = rc.encrypt(pt, &[0xFE, 0x00]);
+
+            assert_ne!(first, second);
+            assert_eq!(rc.decrypt(&first, &[0xFF, 0x00]), pt);
+        }
     }
 }
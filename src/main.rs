@@ -1,53 +1,152 @@
-use crate::lcg::LCG;
-use crate::md5::MD5;
-use crate::utils::unique;
 use crate::Module::RC5;
+use carapace::bbs::Bbs;
+use carapace::checksum::{Algorithm, Entry, Report, Status};
+use carapace::distinct::Distinct;
+use carapace::distributions::Sampler;
+use carapace::generator::{to_unit, Generator};
+use carapace::hmac::Hmac;
+use carapace::lcg::{Insecure, LCG};
+use carapace::md5::Md5;
+use carapace::mt19937::MT19937;
+use carapace::nist::bits_from_bytes;
+use carapace::output::Format;
+use carapace::pcg::PCG32;
+use carapace::presets::{GlibcRand, Preset, PRESETS};
+use carapace::spectral::Spectral;
+use carapace::stats::TestResult;
+use carapace::xorshift::{Xorshift64, Xoshiro256};
+use carapace::{
+    attack, extension, md5, multibuffer, nist, output, plot, rc5, spectral, stats, utils,
+};
 use std::env::args;
 use std::fs::File;
-use std::io::{BufReader, Read, Write};
+use std::io::{self, BufReader, BufWriter, ErrorKind, Read, Write};
 use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
 use std::time::Instant;
 
 // external crates
-use dsa;
 use dsa::pkcs8::der::Decode;
 use dsa::pkcs8::{
     DecodePrivateKey, DecodePublicKey, EncodePrivateKey, EncodePublicKey, LineEnding,
 };
-use dsa::signature::{DigestVerifier, Error, RandomizedDigestSigner, SignatureEncoding};
+use dsa::signature::{DigestVerifier, RandomizedDigestSigner, SignatureEncoding};
 use dsa::{Components, KeySize, Signature, SigningKey, VerifyingKey};
+use rand::SeedableRng;
+use rsa::rand_core::CryptoRngCore;
 use rsa::{Pkcs1v15Encrypt, RsaPrivateKey, RsaPublicKey};
 
-use sha1::{Digest, Sha1};
-
-mod lcg;
-mod md5;
-mod rc5;
-mod utils;
+use carapace::sha1::Sha1;
+use carapace::sha2::{Sha224, Sha256, Sha384, Sha512, Sha512_256};
+use digest::core_api::BlockSizeUser;
+use digest::{Digest, FixedOutput, Update};
+
+// Decimal output is read by people, the other formats by tools: for those stdout carries
+// only the numbers and whatever is reported along with them goes to stderr
+macro_rules! report {
+    ($config:expr, $($arg:tt)*) => {
+        if $config.format == Format::Decimal {
+            println!($($arg)*)
+        } else {
+            eprintln!($($arg)*)
+        }
+    };
+}
 
 const PEM_PRIVATE_KEY: &str = include_str!("../private.pem");
 const PEM_PUBLIC_KEY: &str = include_str!("../public.pem");
 
 enum Module {
-    LCG(u64, u64, u64, u64),
-    MD5(String),
-    RC5(String, String, String, String),
-    RSA(String),
-    DSA(String, String),
+    Lcg(u64, u64, u64, u64),
+    Preset(&'static Preset, u64),
+    GlibcRand(u32),
+    // input of -md5, -sha256 and the other hashes
+    Hash(String),
+    // file paths, "-" for stdin
+    HashFiles(Vec<String>),
+    // checksum lists to verify
+    HashCheck(Vec<String>),
+    // key, file and the tag to verify
+    Hmac(String, String, Option<String>),
+    // message size in bytes, number of messages
+    MD5Bench(usize, usize),
+    // digest of secret || message, secret length, message, extension
+    Extend(md5::Digest, usize, String, String),
+    RC5(String, String, Vec<u8>, String),
+    Rsa(String),
+    Dsa(String, String),
+    Nist(String),
+    Spectral(u64, Vec<u64>),
+    MT(u32),
+    Xorshift(u64),
+    Xoshiro(u64),
+    Pcg(u64, u64),
+    Bbs(u64, u64, u64),
+    Attack(Vec<u64>),
+}
+
+// -dist parameters, shuffle and reservoir work on the indices 0..n
+enum Distribution {
+    Range(u64, u64),
+    Uniform,
+    Normal(f64, f64),
+    Exponential(f64),
+    Poisson(f64),
+    Shuffle,
+    Reservoir(usize),
 }
 
 struct Config {
     module: Module,
     num: usize,
     unique: bool,
+    // the most steps the cycle search may take
+    period: Option<u64>,
+    threads: usize,
+    seed: Option<u64>,
+    stats: bool,
+    nist: bool,
+    attack: bool,
+    truncated: Option<u32>,
+    save: Option<String>,
+    format: Format,
+    dist: Option<Distribution>,
+    plot: Option<String>,
+    plot3d: Option<(String, f64, f64)>,
+    histogram: Option<usize>,
+    // hash of -r, -f and -c
+    algorithm: Algorithm,
+    // SHA-1 files are checked for collision attacks
+    detect_collisions: bool,
+    // md5sum -c: no OK lines, or no output at all
+    quiet: bool,
+    status: bool,
 }
 
 impl Config {
     pub fn new() -> Self {
         Self {
-            module: Module::LCG(0, 0, 0, 0),
+            module: Module::Lcg(0, 0, 0, 0),
             num: 0,
             unique: false,
+            period: None,
+            threads: 1,
+            seed: None,
+            stats: false,
+            nist: false,
+            attack: false,
+            truncated: None,
+            save: None,
+            format: Format::Decimal,
+            dist: None,
+            plot: None,
+            plot3d: None,
+            histogram: None,
+            algorithm: Algorithm::Md5,
+            detect_collisions: false,
+            quiet: false,
+            status: false,
         }
     }
     pub fn set_num(&mut self, num: usize) {
@@ -56,12 +155,63 @@ impl Config {
     pub fn set_unique(&mut self, unique: bool) {
         self.unique = unique;
     }
+    pub fn set_period(&mut self, period: Option<u64>) {
+        self.period = period;
+    }
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads;
+    }
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = Some(seed);
+    }
+    pub fn set_stats(&mut self, stats: bool) {
+        self.stats = stats;
+    }
+    pub fn set_nist(&mut self, nist: bool) {
+        self.nist = nist;
+    }
+    pub fn set_attack(&mut self, attack: bool) {
+        self.attack = attack;
+    }
+    pub fn set_truncated(&mut self, shift: u32) {
+        self.truncated = Some(shift);
+    }
+    pub fn set_save(&mut self, file: String) {
+        self.save = Some(file);
+    }
+    pub fn set_format(&mut self, format: Format) {
+        self.format = format;
+    }
+    pub fn set_dist(&mut self, distribution: Distribution) {
+        self.dist = Some(distribution);
+    }
+    pub fn set_plot(&mut self, file: String) {
+        self.plot = Some(file);
+    }
+    pub fn set_plot3d(&mut self, file: String, yaw: f64, pitch: f64) {
+        self.plot3d = Some((file, yaw, pitch));
+    }
+    pub fn set_histogram(&mut self, bins: usize) {
+        self.histogram = Some(bins.max(1));
+    }
+    pub fn set_algorithm(&mut self, algorithm: Algorithm) {
+        self.algorithm = algorithm;
+    }
+    pub fn set_detect_collisions(&mut self, detect_collisions: bool) {
+        self.detect_collisions = detect_collisions;
+    }
+    pub fn set_quiet(&mut self, quiet: bool) {
+        self.quiet = quiet;
+    }
+    pub fn set_status(&mut self, status: bool) {
+        self.status = status;
+    }
     pub fn set_module(&mut self, module: Module) {
         self.module = module;
     }
 }
 
-fn parse_args(args: &Vec<String>) -> Config {
+fn parse_args(args: &[String]) -> Config {
     let mut config = Config::new();
 
     for (index, arg) in args.iter().enumerate() {
@@ -70,8 +220,8 @@ fn parse_args(args: &Vec<String>) -> Config {
             "-lcg" => {
                 let modulus = args[index + 1].split("^").collect::<Vec<&str>>();
 
-                let mut base = 0;
-                let mut power = 0;
+                let mut base;
+                let mut power;
 
                 if modulus.len() == 2 {
                     base = modulus[0]
@@ -115,7 +265,75 @@ fn parse_args(args: &Vec<String>) -> Config {
                     .parse::<u64>()
                     .expect("Unable to parse seed parameter");
 
-                config.set_module(Module::LCG(modulus, multiplier, increment, seed));
+                config.set_module(Module::Lcg(modulus, multiplier, increment, seed));
+            }
+            "-preset" => {
+                let name = &args[index + 1];
+
+                // srand() starts from 1 unless told otherwise
+                let seed = args
+                    .get(index + 2)
+                    .filter(|next| !next.starts_with('-'))
+                    .map_or(1, |seed| {
+                        seed.parse::<u64>().expect("Unable to parse seed of preset")
+                    });
+
+                // glibc rand() is no LCG, it has a generator of its own
+                if name == "glibc" {
+                    let seed = u32::try_from(seed).expect("Seed of glibc is an unsigned int");
+                    config.set_module(Module::GlibcRand(seed));
+                } else {
+                    let preset = Preset::find(name).unwrap_or_else(|| {
+                        let names = PRESETS.map(|preset| preset.name);
+                        panic!(
+                            "Unknown preset {}, expected glibc or one of {}",
+                            name,
+                            names.join(", ")
+                        )
+                    });
+
+                    config.set_module(Module::Preset(preset, seed));
+                }
+            }
+            "-mt" => {
+                let seed = args[index + 1]
+                    .parse::<u32>()
+                    .expect("Unable to parse seed of Mersenne Twister");
+
+                config.set_module(Module::MT(seed));
+            }
+            "-xorshift" => {
+                let seed = args[index + 1]
+                    .parse::<u64>()
+                    .expect("Unable to parse seed of xorshift");
+
+                config.set_module(Module::Xorshift(seed));
+            }
+            "-xoshiro" => {
+                let seed = args[index + 1]
+                    .parse::<u64>()
+                    .expect("Unable to parse seed of xoshiro");
+
+                config.set_module(Module::Xoshiro(seed));
+            }
+            "-pcg" => {
+                let seed = args[index + 1]
+                    .parse::<u64>()
+                    .expect("Unable to parse seed of pcg");
+                let stream = args[index + 2]
+                    .parse::<u64>()
+                    .expect("Unable to parse stream of pcg");
+
+                config.set_module(Module::Pcg(seed, stream));
+            }
+            "-bbs" => {
+                let p = parse_power(&args[index + 1], "p");
+                let q = parse_power(&args[index + 2], "q");
+                let seed = args[index + 3]
+                    .parse::<u64>()
+                    .expect("Unable to parse seed of bbs");
+
+                config.set_module(Module::Bbs(p, q, seed));
             }
             "-n" => {
                 let num = args[index + 1]
@@ -124,88 +342,251 @@ fn parse_args(args: &Vec<String>) -> Config {
 
                 config.set_num(num);
             }
-            "-u" => {
-                config.set_unique(true);
+            "-format" => {
+                let format = args[index + 1]
+                    .parse::<Format>()
+                    .unwrap_or_else(|err| panic!("Unable to parse output format: {}", err));
+
+                config.set_format(format);
             }
-            "-m" => {}
-            "-md5" => {
-                config.set_unique(false);
-                config.set_num(0);
+            "-dist" => {
+                let parameter = |offset: usize| {
+                    args[index + offset]
+                        .parse::<f64>()
+                        .expect("Unable to parse distribution parameter")
+                };
 
-                let input = args[index + 1]
-                    .parse::<String>()
-                    .expect("Unable to read string input to hash");
+                let distribution = match args[index + 1].as_str() {
+                    "range" => Distribution::Range(parameter(2) as u64, parameter(3) as u64),
+                    "uniform" => Distribution::Uniform,
+                    "normal" => Distribution::Normal(parameter(2), parameter(3)),
+                    "exponential" => Distribution::Exponential(parameter(2)),
+                    "poisson" => Distribution::Poisson(parameter(2)),
+                    "shuffle" => Distribution::Shuffle,
+                    "reservoir" => Distribution::Reservoir(parameter(2) as usize),
+                    other => panic!("Unknown distribution '{}'", other),
+                };
 
-                config.set_module(Module::MD5(String::from("")));
+                config.set_dist(distribution);
             }
-            "-r" => {
-                let input = args[index + 1]
+            "-plot" => {
+                let file = args[index + 1]
                     .parse::<String>()
-                    .expect("Unable to read string input to hash");
+                    .expect("Unable to read plot file path");
 
-                config.set_module(Module::MD5(input));
+                config.set_plot(file);
             }
-            "-f" => {
+            "-plot3d" => {
                 let file = args[index + 1]
                     .parse::<String>()
-                    .expect("Unable to read file path");
+                    .expect("Unable to read plot file path");
 
-                let file = File::open(file).expect("Unable to read input file");
-                let mut buf_reader = BufReader::new(file);
+                // optional viewing angles in degrees
+                let angles = args[index + 2..]
+                    .iter()
+                    .take(2)
+                    .take_while(|arg| !arg.starts_with('-'))
+                    .map(|arg| arg.parse::<f64>().expect("Unable to parse plot angle"))
+                    .collect::<Vec<f64>>();
 
-                let mut contents = String::new();
-                buf_reader
-                    .read_to_string(&mut contents)
-                    .expect("Unable to read file content");
+                let yaw = angles.first().copied().unwrap_or(30.0);
+                let pitch = angles.get(1).copied().unwrap_or(20.0);
 
-                config.set_module(Module::MD5(contents));
+                config.set_plot3d(file, yaw, pitch);
             }
-            "-c" => {
-                // -md5 -c "raw-file.txt" "hashed-file.txt"
+            "-histogram" => {
+                let bins = args
+                    .get(index + 1)
+                    .filter(|next| !next.starts_with('-'))
+                    .map(|bins| {
+                        bins.parse::<usize>()
+                            .expect("Unable to parse histogram bins")
+                    })
+                    .unwrap_or(20);
+
+                config.set_histogram(bins);
+            }
+            "-u" => {
+                config.set_unique(true);
+            }
+            "-period" => {
+                // 2^26 steps take seconds, a number after -period allows more
+                let max_steps = args
+                    .get(index + 1)
+                    .and_then(|arg| arg.parse::<u64>().ok())
+                    .unwrap_or(1 << 26);
+
+                config.set_period(Some(max_steps));
+            }
+            "-stats" => {
+                config.set_stats(true);
+            }
+            "-nist" => {
+                // -nist file.bin tests the file, a bare -nist tests the configured -lcg
+                match args.get(index + 1).filter(|next| !next.starts_with('-')) {
+                    Some(file) => config.set_module(Module::Nist(file.clone())),
+                    None => config.set_nist(true),
+                }
+            }
+            "-attack" => {
+                // -attack x0 x1 ... cracks the given outputs, a bare -attack the configured -lcg
+                let outputs = args[index + 1..]
+                    .iter()
+                    .take_while(|arg| !arg.starts_with('-'))
+                    .map(|arg| arg.parse::<u64>().expect("Unable to parse observed output"))
+                    .collect::<Vec<u64>>();
+
+                if outputs.is_empty() {
+                    config.set_attack(true);
+                } else {
+                    config.set_module(Module::Attack(outputs));
+                }
+            }
+            "-truncated" => {
+                let shift = args[index + 1]
+                    .parse::<u32>()
+                    .expect("Unable to parse number of hidden low bits");
 
-                // Reading raw-file to make from it a hash
-                let raw_file = args[index + 1]
+                config.set_truncated(shift);
+            }
+            "-save" => {
+                let file = args[index + 1]
                     .parse::<String>()
-                    .expect("Unable to read file path");
+                    .expect("Unable to read file path to save state");
 
-                let file = File::open(raw_file).expect("Unable to read raw file");
-                let mut buf_reader = BufReader::new(file);
+                config.set_save(file);
+            }
+            "-resume" => {
+                let file = args[index + 1]
+                    .parse::<String>()
+                    .expect("Unable to read file path with saved state");
+
+                let json = std::fs::read_to_string(file).expect("Unable to read saved state");
+                let lcg = LCG::from_json(&json)
+                    .unwrap_or_else(|err| panic!("Unable to resume LCG: {}", err));
+
+                config.set_module(Module::Lcg(
+                    lcg.modulus(),
+                    lcg.multiplier(),
+                    lcg.increment(),
+                    lcg.state(),
+                ));
+            }
+            "-threads" => {
+                let threads = args[index + 1]
+                    .parse::<usize>()
+                    .expect("Unable to parse number of threads");
 
-                let mut raw_contents = String::new();
-                buf_reader
-                    .read_to_string(&mut raw_contents)
-                    .expect("Unable to read raw file content");
+                config.set_threads(threads.max(1));
+            }
+            "-seed" => {
+                let seed = args[index + 1]
+                    .parse::<u64>()
+                    .expect("Unable to parse seed for rsa/dsa");
 
-                let raw_file_hash = MD5::from(raw_contents.as_str());
+                config.set_seed(seed);
+            }
+            "-spectral" => {
+                // same modulus notation as -lcg, followed by the candidate multipliers
+                let modulus = parse_power(&args[index + 1], "modulus") - 1;
 
-                // Reading raw-file to make from it a hash
-                let hashed_file = args[index + 2]
-                    .parse::<String>()
-                    .expect("Unable to read file path");
+                let multipliers = args[index + 2..]
+                    .iter()
+                    .take_while(|arg| !arg.starts_with('-'))
+                    .map(|arg| parse_power(arg, "multiplier"))
+                    .collect::<Vec<u64>>();
 
-                let file = File::open(hashed_file).expect("Unable to read file that contains hash");
-                let mut buf_reader = BufReader::new(file);
+                config.set_module(Module::Spectral(modulus, multipliers));
+            }
+            "-m" => {}
+            "-hmac" => {
+                // -hmac key file [tag], checks the tag when there is one
+                let key = args[index + 1].clone();
+                let file = args
+                    .get(index + 2)
+                    .expect("Unable to read file to authenticate")
+                    .clone();
+                let tag = args
+                    .get(index + 3)
+                    .filter(|next| !next.starts_with('-'))
+                    .cloned();
+
+                config.set_module(Module::Hmac(key, file, tag));
+            }
+            "-md5bench" => {
+                // -md5bench [size] [count], 64 KiB times 4096 by default
+                let size = args
+                    .get(index + 1)
+                    .filter(|next| !next.starts_with('-'))
+                    .map(|size| size.parse::<usize>().expect("Unable to read message size"))
+                    .unwrap_or(1 << 16);
+                let count = args
+                    .get(index + 2)
+                    .filter(|next| !next.starts_with('-'))
+                    .map(|count| {
+                        count
+                            .parse::<usize>()
+                            .expect("Unable to read message count")
+                    })
+                    .unwrap_or(1 << 12);
+
+                config.set_module(Module::MD5Bench(size, count));
+            }
+            "-extend" => {
+                // -extend digest secret_length message extension
+                let digest = args[index + 1]
+                    .parse::<md5::Digest>()
+                    .expect("Unable to parse MD5 digest");
+                let secret_length = args[index + 2]
+                    .parse::<usize>()
+                    .expect("Unable to read secret length");
+                let message = args[index + 3].clone();
+                let extension = args[index + 4].clone();
 
-                let mut hashed_contents = String::new();
-                buf_reader
-                    .read_to_string(&mut hashed_contents)
-                    .expect("Unable to read file with hash");
+                config.set_module(Module::Extend(digest, secret_length, message, extension));
+            }
+            "-md5" | "-sha1" | "-sha224" | "-sha256" | "-sha384" | "-sha512" | "-sha512_256" => {
+                config.set_unique(false);
+                config.set_num(0);
+                config.set_algorithm(arg[1..].parse().expect("Unable to read hash name"));
 
-                if raw_file_hash == hashed_contents {
-                    println!(
-                        "\x1b[32mFile {} content is equal to hash that contains in file {}\x1b[0m",
-                        args[index + 1],
-                        args[index + 2]
-                    );
-                } else {
-                    println!(
-                        "\x1b[31mFile {} content is not equal to hash that contains in file {}\x1b[0m",
-                        args[index + 1],
-                        args[index + 2]
-                    );
-                }
+                config.set_module(Module::Hash(String::from("")));
+            }
+            "-r" => {
+                let input = args[index + 1]
+                    .parse::<String>()
+                    .expect("Unable to read string input to hash");
 
-                config.set_module(Module::MD5(raw_contents));
+                config.set_module(Module::Hash(input));
+            }
+            "-f" => {
+                // md5sum or sha256sum style, "-" is stdin
+                let files = args[index + 1..]
+                    .iter()
+                    .take_while(|arg| *arg == "-" || !arg.starts_with('-'))
+                    .cloned()
+                    .collect::<Vec<String>>();
+
+                config.set_module(Module::HashFiles(files));
+            }
+            "-c" => {
+                // checksum lists written by -f, md5sum or sha256sum
+                let lists = args[index + 1..]
+                    .iter()
+                    .take_while(|arg| *arg == "-" || !arg.starts_with('-'))
+                    .cloned()
+                    .collect::<Vec<String>>();
+
+                config.set_module(Module::HashCheck(lists));
+            }
+            "--detect-collisions" => {
+                config.set_detect_collisions(true);
+            }
+            "--quiet" => {
+                config.set_quiet(true);
+            }
+            "--status" => {
+                config.set_status(true);
             }
             "-rc5" => {
                 let mode = args[index + 1]
@@ -215,36 +596,23 @@ fn parse_args(args: &Vec<String>) -> Config {
                     .parse::<String>()
                     .expect("Unable to read rc5 cipher mode");
 
-                let file_path = match args[index + 3].parse::<String>() {
-                    Ok(file) => file,
-                    Err(_) => String::from(""),
-                };
+                let file_path = args[index + 3].parse::<String>().unwrap_or_default();
 
-                // file
+                // file, read as bytes so ciphertext can be decrypted
 
-                let mut contents = String::new();
+                let mut contents = Vec::new();
 
                 let path = Path::new(file_path.as_str());
 
                 if path.exists() {
-                    let file = File::open(file_path).expect("Unable to read input file");
-
-                    let mut buf_reader = BufReader::new(file);
-
-                    buf_reader
-                        .read_to_string(&mut contents)
-                        .expect("Unable to read file content");
+                    contents = std::fs::read(path).expect("Unable to read input file");
                 }
 
-                let mut input = String::new();
-
-                if contents != "" {
-                    input = contents;
+                let input = if !contents.is_empty() {
+                    contents
                 } else {
-                    input = args[index + 3]
-                        .parse::<String>()
-                        .expect("Unable to read raw input!");
-                }
+                    args[index + 3].as_bytes().to_vec()
+                };
 
                 let key = args[index + 4]
                     .parse::<String>()
@@ -271,7 +639,7 @@ fn parse_args(args: &Vec<String>) -> Config {
                         .expect("Unable to read file content");
                 }
 
-                config.set_module(Module::RSA(contents));
+                config.set_module(Module::Rsa(contents));
             }
             "-dsa" => {
                 let mode = args[index + 1]
@@ -319,7 +687,7 @@ fn parse_args(args: &Vec<String>) -> Config {
                 //     Err(_) => None,
                 // };
 
-                config.set_module(Module::DSA(mode, contents));
+                config.set_module(Module::Dsa(mode, contents));
             }
             _ => {
                 // panic!("Unexpected flag: '{}'", arg.as_str())
@@ -332,81 +700,740 @@ fn parse_args(args: &Vec<String>) -> Config {
 
 // lcg - cargo run -- -lcg 2^11 3^5 1 4 -u -n 100000 > nums.txt -> 88 unique of 2047
 // lcg - cargo run -- -lcg 65538 75 74 0 -u -n 100000 > nums.txt -> 65000+ unique
+// lcg - cargo run -- -lcg 2^27 1103515245 12345 0 -u -n 100000000 -format binary > nums.bin -> exact, 16 MiB bitset, count on stderr
+// lcg - cargo run -- -lcg 2^31 16807 0 1 -u -n 100000000 -format binary > nums.bin -> HyperLogLog estimate on stderr
+// lcg - cargo run -- -lcg 2^63 6364136223846793005 1 0 -u -n 1000000 -> ~1000000, HyperLogLog estimate
+// lcg - cargo run -- -lcg 2^11 3^5 1 4 -period -> tail 0, cycle 88, Hull-Dobell false
+// lcg - cargo run -- -lcg 2^31 16807 0 1 -period 10000000000 -> cycle 2147483646 after a minute or so
+// lcg - cargo run -- -lcg 2^31 16807 0 1 -n 100000000 -threads 8 > nums.txt
+// lcg - cargo run -- -lcg 2^31 16807 0 1 -n 100000000 -format binary | RNG_test stdin32
+// lcg - cargo run -- -lcg 2^31 16807 0 1 -n 100 -format csv > nums.csv -> also lines, hex
+// lcg - cargo run -- -lcg 2^31 16807 0 1 -n 100000 -stats -> Knuth test battery
+// lcg - cargo run -- -lcg 2^11 3^5 1 4 -n 2047 -plot pairs.svg -> the points lie on lines
+// lcg - cargo run -- -lcg 2147483649 65539 0 1 -n 20000 -plot3d randu.ppm 32.8 10 -> RANDU planes
+// lcg - cargo run -- -lcg 2^11 3^5 1 4 -n 2047 -histogram 16 -> ASCII histogram
+// lcg - cargo run -- -lcg 2^31 16807 0 1 -n 1000 -save state.json -> checkpoint after 1000
+// lcg - cargo run -- -resume state.json -n 1000 -> continues with the 1001st number
+// lcg - cargo run -- -lcg 2^31 16807 0 1 -nist -> NIST SP 800-22 on 10^6 bits
+// dist - cargo run -- -lcg 2^31 48271 0 1 -n 10 -dist range 1 7 -> fair dice rolls
+// dist - cargo run -- -xoshiro 42 -n 1000 -dist normal 0 1 -> also uniform, exponential 2, poisson 4
+// dist - cargo run -- -pcg 42 54 -n 52 -dist shuffle -> random permutation of 0..52
+// dist - cargo run -- -mt 5489 -n 1000000 -dist reservoir 5 -> 5 of 0..1000000
+// preset - cargo run -- -preset msvc -n 3 -> 41 18467 6334, rand() after srand(1)
+// preset - cargo run -- -preset java 42 -n 1 -> 3124862261, new Random(42).nextInt() as unsigned
+// preset - cargo run -- -preset glibc -n 3 -> 1804289383 846930886 1681692777, rand() after srand(1)
+// preset - cargo run -- -preset minstd -n 100000 -stats -> also minstd_rand, glibc_type0, nr, mmix
+// mt - cargo run -- -mt 5489 -n 10 -> 3499211612 581869302 ...
+// xorshift - cargo run -- -xorshift 88172645463325252 -n 100000 -u -stats
+// xoshiro - cargo run -- -xoshiro 42 -n 100000 -stats -> xoshiro256** seeded by SplitMix64
+// pcg - cargo run -- -pcg 42 54 -n 6 -> pcg32 with state 42 on stream 54
+// bbs - cargo run -- -bbs 1000003 2001911 12345 -n 64 -nist -> one bit per output
+// attack - cargo run -- -lcg 65539 75 74 0 -attack -> predicts the rc5 CBC IV generator
+// attack - cargo run -- -lcg 2^31 48271 11 5 -attack -truncated 16 -> only high bits seen
+// attack - cargo run -- -attack 5624 28646 51308 46970 49310 28196 17558 6164 -n 5
+// nist - cargo run -- -nist ciphertext.bin -> NIST SP 800-22 on file bits
+// spectral - cargo run -- -spectral 2^31 16807 48271 65539 -> multipliers for m = 2^31 - 1, best first
 // md5 - cargo run -- -md5 "" -> input raw
 // md5 - cargo run -- -md5 file.txt >> hash.txt -> input file
-// md5 - cargo run -- -md5 file.txt hash.txt -> compare raw and hash
+// md5 - cat big.iso | cargo run -- -md5 -f - -> stdin, hashed in constant memory
+// md5 - cargo run -- -md5 -f ciphertext.txt -> any bytes, not only UTF-8
+// sha1 - cargo run -- -sha1 -f shattered-1.pdf --detect-collisions -> refuses files made by a SHA-1 collision attack
+// sha256 - cargo run -- -sha256 -r "abc" -> also -sha224, -sha384, -sha512 and -sha512_256
+// sha256 - cargo run -- -sha256 -f a.txt b.txt > sums.sha256 -> sha256sum format, -c checks it
+// md5bench - cargo run --release -- -md5bench 64 1000000 -> MB/s of one MD5 at a time and of every SIMD engine
+// extend - cargo run -- -extend c8a8df519e646ab8b43c1c880db85d13 6 "user=bob" "&admin=1" -> MD5("secret" || forged) and the forged message in hex
+// hmac - cargo run -- -hmac key file.txt -> HMAC-MD5 tag of the file
+// hmac - cargo run -- -sha256 -hmac key file.txt -> HMAC-SHA256, any hash option works
+// hmac - cargo run -- -hmac Jefe nothing.txt 750c783e6ab0b503eaa86e310a5db738 -> Verified! (RFC 2202 case 2)
+// md5 - cargo run -- -md5 -f a.txt b.txt > sums.md5 -> md5sum format, one line per file
+// md5 - cargo run --release -- -md5 -f src/*.rs -> files up to 1 MiB hashed side by side by the multi-buffer engine
+// md5 - cargo run -- -md5 -c sums.md5 -> OK/FAILED per file, exit code 1 on any failure
+// md5 - cargo run -- -md5 -c sums.md5 --quiet -> failures only, --status prints nothing
 // rc5 - cargo run -- -rc5 -ecb encrypt/decrypt "test" key > file.txt
 // rc5 - cargo run -- -rc5 -cbc encrypt/decrypt plain.txt key > ciphertext.txt
 // rc5 - cargo run -- -rc5 -cbc_md5 encrypt/decrypt plain.txt key > ciphertext.txt
 // rsa - cargo run -- -rsa file.txt
+// rsa - cargo run -- -rsa file.txt -seed 42 -> reproducible keys from LCG, demo only
 // dsa - cargo run -- -dsa "generate"
+// dsa - cargo run -- -dsa "generate" -seed 42
 // dsa - cargo run -- -dsa "sign" "message"
 // dsa - cargo run -- -dsa "verify" "message"
 
+// "-" reads stdin
+fn open_input(file: &str) -> io::Result<Box<dyn Read>> {
+    if file == "-" {
+        Ok(Box::new(io::stdin().lock()))
+    } else {
+        Ok(Box::new(File::open(file)?))
+    }
+}
+
+// Hashes the file in constant memory, a detected SHA-1 collision attack is an error
+fn hash_file(algorithm: Algorithm, file: &str, detect_collisions: bool) -> io::Result<Vec<u8>> {
+    if algorithm == Algorithm::Sha1 && detect_collisions {
+        let mut sha1 = Sha1::with_collision_detection();
+        io::copy(&mut open_input(file)?, &mut sha1)?;
+
+        return sha1
+            .finalize_checked()
+            .map(|digest| digest.as_bytes().to_vec())
+            .map_err(|collision| io::Error::new(io::ErrorKind::InvalidData, collision));
+    }
+
+    algorithm.digest_reader(open_input(file)?)
+}
+
+// Tag of a file or stdin under the hash -md5, -sha256 and the others chose
+fn hmac_file<D: Update + FixedOutput + BlockSizeUser + Default + Clone>(
+    key: &[u8],
+    file: &str,
+) -> Vec<u8> {
+    let mut hmac = Hmac::<D>::new(key);
+    open_input(file)
+        .and_then(|mut input| io::copy(&mut input, &mut hmac))
+        .expect("Unable to read input to authenticate");
+
+    hmac.finalize().to_vec()
+}
+
+// Small files are read whole and their MD5s computed side by side by the multi-buffer
+// engine, stdin, large files and the other hashes go through hash_file one by one
+fn hash_files(
+    algorithm: Algorithm,
+    files: &[String],
+    detect_collisions: bool,
+) -> Vec<io::Result<Vec<u8>>> {
+    const SMALL_FILE: u64 = 1 << 20;
+
+    let mut digests = Vec::with_capacity(files.len());
+    let mut small = Vec::new();
+    let mut contents = Vec::new();
+
+    for file in files {
+        let whole = algorithm == Algorithm::Md5
+            && file != "-"
+            && std::fs::metadata(file).is_ok_and(|meta| meta.is_file() && meta.len() <= SMALL_FILE);
+
+        if !whole {
+            digests.push(hash_file(algorithm, file, detect_collisions));
+            continue;
+        }
+
+        match std::fs::read(file) {
+            Ok(bytes) => {
+                small.push(digests.len());
+                contents.push(bytes);
+                digests.push(Ok(Vec::new()));
+            }
+            Err(err) => digests.push(Err(err)),
+        }
+    }
+
+    let inputs = contents.iter().map(Vec::as_slice).collect::<Vec<_>>();
+    for (index, digest) in small.into_iter().zip(multibuffer::digest_many(&inputs)) {
+        digests[index] = Ok(digest.as_bytes().to_vec());
+    }
+
+    digests
+}
+
+// md5sum -c: every listed file is hashed again, the exit code tells if all matched
+fn check_sums(
+    algorithm: Algorithm,
+    lists: &[String],
+    quiet: bool,
+    status: bool,
+    detect_collisions: bool,
+) {
+    let program = algorithm.name();
+
+    let mut report = Report::default();
+
+    for list in lists {
+        let mut contents = String::new();
+        open_input(list)
+            .and_then(|mut input| input.read_to_string(&mut contents))
+            .unwrap_or_else(|err| panic!("Unable to read checksum list {}: {}", list, err));
+
+        // blank lines and comments are skipped, like md5sum does
+        for line in contents.lines() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let Some(entry) = Entry::parse(line, algorithm) else {
+                report.malformed += 1;
+                continue;
+            };
+
+            let outcome = match hash_file(algorithm, &entry.file, detect_collisions) {
+                Ok(digest) if digest == entry.digest => Status::Ok,
+                Ok(_) => Status::Failed,
+                Err(err) => {
+                    if !status {
+                        eprintln!("{}: {}: {}", program, entry.file, err);
+                    }
+
+                    Status::Missing
+                }
+            };
+
+            report.record(outcome);
+
+            if !status && (!quiet || outcome != Status::Ok) {
+                println!("{}: {}", entry.file, outcome);
+            }
+        }
+    }
+
+    if !status {
+        for warning in report.warnings(algorithm) {
+            eprintln!("{}: {}", program, warning);
+        }
+    }
+
+    if !report.passed() {
+        std::process::exit(1);
+    }
+}
+
+fn print_results(config: &Config, results: &[TestResult]) {
+    report!(
+        config,
+        "{:<36} {:>14} {:>10}  Result",
+        "Test",
+        "Statistic",
+        "p-value"
+    );
+
+    for result in results {
+        report!(
+            config,
+            "{:<36} {:>14.4} {:>10.6}  {}",
+            result.name,
+            result.statistic,
+            result.p_value,
+            if result.passed() { "PASS" } else { "FAIL" }
+        );
+    }
+}
+
+// Streams the numbers to stdout in the -format chosen, -u counts them on the way
+fn print_numbers<I: Iterator<Item = u64>>(config: &Config, modulus: Option<u64>, numbers: I) {
+    let mut distinct = config.unique.then(|| Distinct::new(modulus));
+    let numbers = numbers.inspect(|number| {
+        if let Some(distinct) = distinct.as_mut() {
+            distinct.insert(*number);
+        }
+    });
+
+    let mut writer = BufWriter::new(io::stdout().lock());
+
+    match output::write_numbers(&mut writer, config.format, modulus, numbers) {
+        Ok(()) => {}
+        // the reader, e.g. `head` or a test tool, has seen enough
+        Err(err) if err.kind() == ErrorKind::BrokenPipe => std::process::exit(0),
+        Err(err) => panic!("Unable to write numbers: {}", err),
+    }
+
+    drop(writer);
+
+    match distinct {
+        Some(distinct) if distinct.is_exact() => {
+            report!(config, "Number of unique elements - {}", distinct.count())
+        }
+        Some(distinct) => report!(
+            config,
+            "Number of unique elements - ~{} (HyperLogLog estimate)",
+            distinct.count()
+        ),
+        None => {}
+    }
+}
+
+// -n samples of the -dist distribution, one per line
+fn print_samples<G: Generator>(config: &Config, distribution: &Distribution, generator: G) {
+    let mut sampler = Sampler::new(generator);
+    let mut writer = BufWriter::new(io::stdout().lock());
+
+    let result = match *distribution {
+        Distribution::Range(low, high) => {
+            (0..config.num).try_for_each(|_| writeln!(writer, "{}", sampler.range(low, high)))
+        }
+        Distribution::Uniform => {
+            (0..config.num).try_for_each(|_| writeln!(writer, "{}", sampler.uniform()))
+        }
+        Distribution::Normal(mean, std_dev) => {
+            (0..config.num).try_for_each(|_| writeln!(writer, "{}", sampler.normal(mean, std_dev)))
+        }
+        Distribution::Exponential(rate) => {
+            (0..config.num).try_for_each(|_| writeln!(writer, "{}", sampler.exponential(rate)))
+        }
+        Distribution::Poisson(mean) => {
+            (0..config.num).try_for_each(|_| writeln!(writer, "{}", sampler.poisson(mean)))
+        }
+        Distribution::Shuffle => {
+            let mut items = (0..config.num).collect::<Vec<usize>>();
+            sampler.shuffle(&mut items);
+
+            items
+                .iter()
+                .try_for_each(|item| writeln!(writer, "{}", item))
+        }
+        Distribution::Reservoir(k) => sampler
+            .reservoir(0..config.num, k)
+            .iter()
+            .try_for_each(|item| writeln!(writer, "{}", item)),
+    };
+
+    match result.and_then(|_| writer.flush()) {
+        Ok(()) => {}
+        Err(err) if err.kind() == ErrorKind::BrokenPipe => std::process::exit(0),
+        Err(err) => panic!("Unable to write samples: {}", err),
+    }
+}
+
+// -stats, -nist and the plots, all start from the state the numbers were generated from
+fn print_statistics<G: Generator + Clone>(config: &Config, source: G) {
+    if config.plot.is_some() || config.plot3d.is_some() || config.histogram.is_some() {
+        let count = if config.num > 0 { config.num } else { 4096 };
+        let modulus = source.modulus();
+        let values = source
+            .clone()
+            .take(count)
+            .map(|x| to_unit(x, modulus))
+            .collect::<Vec<f64>>();
+
+        if let Some(file) = &config.plot {
+            save_canvas(file, &plot::pairs(&values, 600));
+        }
+
+        if let Some((file, yaw, pitch)) = &config.plot3d {
+            save_canvas(file, &plot::triples(&values, 600, *yaw, *pitch));
+        }
+
+        if let Some(bins) = config.histogram {
+            report!(config, "{}", plot::histogram(&values, bins, 60).trim_end());
+        }
+    }
+
+    if config.stats {
+        let samples = if config.num > 0 { config.num } else { 100_000 };
+
+        print_results(
+            config,
+            &stats::battery(source.clone(), source.modulus(), samples),
+        );
+    }
+
+    if config.nist {
+        let mut source = source;
+        let mut bytes = vec![0_u8; 1_000_000 / 8];
+        source.fill_bytes(&mut bytes);
+
+        print_results(config, &nist::suite(&bits_from_bytes(&bytes)));
+    }
+}
+
+// .ppm files get a bitmap, anything else SVG
+fn save_canvas(file: &str, canvas: &plot::Canvas) {
+    let contents = if file.ends_with(".ppm") {
+        canvas.to_ppm()
+    } else {
+        canvas.to_svg().into_bytes()
+    };
+
+    std::fs::write(file, contents).expect("Unable to write plot");
+}
+
+fn run_generator<G: Generator + Clone>(config: &Config, generator: G) {
+    match &config.dist {
+        Some(distribution) => print_samples(config, distribution, generator.clone()),
+        None => print_numbers(
+            config,
+            generator.modulus(),
+            generator.clone().take(config.num),
+        ),
+    }
+    print_statistics(config, generator);
+}
+
+// Cracks the first outputs of `lcg` as an eavesdropper would and compares the predictions
+// with what the generator really produces next
+fn attack_demo(config: &Config, lcg: &LCG) {
+    const OBSERVED: usize = 10;
+    const PREDICTED: usize = 5;
+
+    let mut actual = lcg.clone();
+    let states = actual.by_ref().take(OBSERVED).collect::<Vec<u64>>();
+
+    let cracked = match config.truncated {
+        None => {
+            report!(config, "Observed outputs - {:?}", states);
+
+            attack::recover(&states)
+        }
+        Some(shift) => {
+            let observed = states.iter().map(|x| x >> shift).collect::<Vec<u64>>();
+            report!(config, "Observed outputs >> {} - {:?}", shift, observed);
+
+            attack::recover_truncated(
+                &observed,
+                lcg.modulus(),
+                lcg.multiplier(),
+                lcg.increment(),
+                shift,
+            )
+        }
+    };
+
+    match cracked {
+        Some(cracked) => {
+            report!(
+                config,
+                "Recovered modulus - {}, multiplier - {}, increment - {}, state - {}",
+                cracked.modulus(),
+                cracked.multiplier(),
+                cracked.increment(),
+                cracked.state()
+            );
+            report!(
+                config,
+                "Predicted - {:?}",
+                cracked.take(PREDICTED).collect::<Vec<u64>>()
+            );
+            report!(
+                config,
+                "Actual    - {:?}",
+                actual.take(PREDICTED).collect::<Vec<u64>>()
+            );
+        }
+        None => report!(
+            config,
+            "Unable to recover the generator from {} outputs",
+            OBSERVED
+        ),
+    }
+}
+
+// base^power or a plain number
+fn parse_power(input: &str, name: &str) -> u64 {
+    let parts = input.split("^").collect::<Vec<&str>>();
+
+    let base = parts[0]
+        .parse::<u64>()
+        .unwrap_or_else(|_| panic!("Unable to parse base of {}", name));
+    let power = match parts.get(1) {
+        Some(power) => power
+            .parse::<u32>()
+            .unwrap_or_else(|_| panic!("Unable to parse power of {}", name)),
+        None => 1,
+    };
+
+    base.pow(power)
+}
+
+fn print_spectral(ranked: &[(u64, Vec<Spectral>)]) {
+    print!("{:<22}", "Multiplier");
+    for result in &ranked[0].1 {
+        print!(" {:>7}", format!("S{}", result.dimension));
+    }
+    println!();
+
+    for (multiplier, results) in ranked {
+        print!("{:<22}", multiplier);
+        for result in results {
+            print!(" {:>7.4}", result.merit);
+        }
+        println!();
+    }
+}
+
+// Randomness for rsa/dsa: thread_rng by default, a seeded LCG with -seed so demos repeat
+fn demo_rng(seed: Option<u64>) -> Box<dyn CryptoRngCore> {
+    match seed {
+        Some(seed) => Box::new(Insecure(LCG::seed_from_u64(seed))),
+        None => Box::new(rand::thread_rng()),
+    }
+}
+
 fn main() {
-    let args = args().into_iter().collect::<Vec<String>>();
+    let args = args().collect::<Vec<String>>();
 
     let config = parse_args(&args);
 
     match config.module {
-        Module::LCG(modular, multiplier, increment, seed) => {
-            let mut lcg = LCG::new(modular, multiplier, increment, seed);
+        Module::Lcg(modular, multiplier, increment, seed) => {
+            let mut lcg = LCG::try_new(modular, multiplier, increment, seed)
+                .unwrap_or_else(|err| panic!("Invalid LCG parameters: {}", err));
+
+            let period = config
+                .period
+                .map(|max_steps| (lcg.period_within(max_steps), max_steps));
+            let full = lcg.hull_dobell().full_period();
+            let source = lcg.clone();
+
+            if let Some(distribution) = &config.dist {
+                print_samples(&config, distribution, lcg);
+            } else if config.threads > 1 {
+                // chunk i of the sequence is made by thread i % threads and printed in
+                // order as it comes in, so the output matches the single-threaded one and
+                // only a couple of chunks per thread are held at a time
+                const CHUNK: usize = 1 << 16;
+
+                let threads = config.threads;
+                let chunks = config.num.div_ceil(CHUNK);
+                let streams = lcg.split(threads, CHUNK as u64);
+
+                thread::scope(|scope| {
+                    let receivers = streams
+                        .into_iter()
+                        .enumerate()
+                        .map(|(index, mut stream)| {
+                            let (sender, receiver) = mpsc::sync_channel::<Vec<u64>>(2);
+                            let num = config.num;
+
+                            scope.spawn(move || {
+                                for chunk in (index..chunks).step_by(threads) {
+                                    let count = CHUNK.min(num - chunk * CHUNK);
+                                    let values = stream.by_ref().take(count).collect();
+
+                                    if sender.send(values).is_err() {
+                                        break;
+                                    }
+                                    stream.advance(((threads - 1) * CHUNK) as u64);
+                                }
+                            });
+
+                            receiver
+                        })
+                        .collect::<Vec<_>>();
+
+                    let numbers = (0..chunks).flat_map(|chunk| {
+                        receivers[chunk % threads]
+                            .recv()
+                            .expect("Generator thread panicked")
+                    });
+
+                    print_numbers(&config, Some(modular), numbers);
+                });
+            } else {
+                print_numbers(&config, Some(modular), lcg.by_ref().take(config.num));
+            }
+
+            match period {
+                Some((Some(period), _)) => report!(
+                    config,
+                    "Tail length - {}, cycle length - {}",
+                    period.tail,
+                    period.length
+                ),
+                Some((None, max_steps)) => {
+                    report!(
+                        config,
+                        "Tail plus cycle length - at least {}",
+                        max_steps / 3
+                    )
+                }
+                None => {}
+            }
+
+            if config.period.is_some() {
+                report!(config, "Hull-Dobell full period - {}", full);
+            }
+
+            if let Some(file) = &config.save {
+                // the state after the last printed number, whichever way they were made
+                let mut end = source.clone();
+                end.advance(config.num as u64);
+
+                std::fs::write(file, end.to_json()).expect("Unable to save LCG state");
+            }
+
+            if config.attack {
+                attack_demo(&config, &source);
+            }
+
+            print_statistics(&config, source);
+        }
+        Module::Attack(ref outputs) => {
+            let lcg = attack::recover(outputs).expect("Unable to recover LCG from the outputs");
+            let count = if config.num > 0 { config.num } else { 10 };
+
+            report!(
+                config,
+                "Modulus - {}, multiplier - {}, increment - {}",
+                lcg.modulus(),
+                lcg.multiplier(),
+                lcg.increment()
+            );
+            print_numbers(&config, Some(lcg.modulus()), lcg.take(count));
+        }
+        Module::Preset(preset, seed) => match preset.width() {
+            32 => run_generator(&config, preset.generator::<u32>(seed)),
+            64 => run_generator(&config, preset.generator::<u64>(seed)),
+            _ => run_generator(&config, preset.generator::<u128>(seed)),
+        },
+        Module::GlibcRand(seed) => run_generator(&config, GlibcRand::new(seed)),
+        Module::MT(seed) => run_generator(&config, MT19937::new(seed)),
+        Module::Xorshift(seed) => run_generator(&config, Xorshift64::new(seed)),
+        Module::Xoshiro(seed) => run_generator(&config, Xoshiro256::seed_from_u64(seed)),
+        Module::Pcg(seed, stream) => run_generator(&config, PCG32::new(seed, stream)),
+        Module::Bbs(p, q, seed) => {
+            let bbs = Bbs::try_new(p, q, seed)
+                .unwrap_or_else(|err| panic!("Invalid BBS parameters: {}", err));
+
+            run_generator(&config, bbs);
+        }
+        Module::Spectral(modulus, multipliers) => {
+            if multipliers.is_empty() {
+                panic!("No multipliers to rank for modulus {}", modulus);
+            }
+
+            let ranked = spectral::rank(modulus, &multipliers, spectral::MAX_DIMENSION);
 
-            let mut nums = Vec::<u64>::with_capacity(config.num);
+            print_spectral(&ranked);
 
-            // TODO: add threads for big input num
+            // a single candidate also gets the raw distances between hyperplanes
+            if let [(_, results)] = ranked.as_slice() {
+                for result in results {
+                    println!(
+                        "t = {}: nu^2 = {}, nu = {:.2}, mu = {:.4}",
+                        result.dimension,
+                        result.nu_squared,
+                        result.nu(),
+                        result.mu
+                    );
+                }
+            }
+        }
+        Module::Nist(ref file) => {
+            let bytes = std::fs::read(file).expect("Unable to read input file");
+
+            print_results(&config, &nist::suite(&bits_from_bytes(&bytes)));
+        }
+        Module::Hash(input) => {
+            let hash = config.algorithm.digest(input.as_bytes());
+
+            print!("{}", utils::to_hex(&hash).to_uppercase());
+        }
+        Module::HashFiles(files) => {
+            let program = config.algorithm.name();
+            let mut failed = false;
+
+            for batch in files.chunks(64) {
+                let digests = hash_files(config.algorithm, batch, config.detect_collisions);
+
+                for (file, digest) in batch.iter().zip(digests) {
+                    match digest {
+                        Ok(digest) => println!(
+                            "{}",
+                            Entry {
+                                digest,
+                                file: file.clone()
+                            }
+                        ),
+                        Err(err) => {
+                            eprintln!("{}: {}: {}", program, file, err);
+                            failed = true;
+                        }
+                    }
+                }
+            }
 
-            for _ in 0..config.num {
-                let number = lcg.next().expect("Failed generate next number");
-                nums.push(number);
-                print!("{} ", number);
+            if failed {
+                std::process::exit(1);
             }
+        }
+        Module::HashCheck(lists) => check_sums(
+            config.algorithm,
+            &lists,
+            config.quiet,
+            config.status,
+            config.detect_collisions,
+        ),
+        Module::Hmac(key, file, tag) => {
+            let key = key.as_bytes();
+            let computed = match config.algorithm {
+                Algorithm::Md5 => hmac_file::<Md5>(key, &file),
+                Algorithm::Sha1 => hmac_file::<Sha1>(key, &file),
+                Algorithm::Sha224 => hmac_file::<Sha224>(key, &file),
+                Algorithm::Sha256 => hmac_file::<Sha256>(key, &file),
+                Algorithm::Sha384 => hmac_file::<Sha384>(key, &file),
+                Algorithm::Sha512 => hmac_file::<Sha512>(key, &file),
+                Algorithm::Sha512_256 => hmac_file::<Sha512_256>(key, &file),
+            };
 
-            println!();
+            match tag {
+                None => print!("{}", utils::to_hex(&computed).to_uppercase()),
+                Some(tag) => {
+                    let tag = utils::from_hex(&tag).expect("Unable to parse tag as hex");
 
-            if config.unique {
-                println!("Number of unique elements - {}", unique(&nums));
+                    if utils::constant_time_eq(&computed, &tag) {
+                        println!("Verified!");
+                    } else {
+                        println!("Not verified!");
+                    }
+                }
             }
         }
-        Module::MD5(input) => {
-            let hash = md5::MD5::from(input.as_str());
+        Module::MD5Bench(size, count) => {
+            println!("{} messages of {} bytes", count, size);
 
-            print!("{}", hash);
+            for throughput in multibuffer::benchmark(size, count) {
+                println!("{}", throughput);
+            }
+        }
+        Module::Extend(digest, secret_length, message, extension) => {
+            let forgery = extension::forge(
+                digest,
+                secret_length,
+                message.as_bytes(),
+                extension.as_bytes(),
+            );
+
+            // the glue padding is binary, so the message is printed as hex
+            println!("{:x}", forgery.digest);
+            println!("{}", utils::to_hex(&forgery.message));
         }
         Module::RC5(mode, cipher_mode, input, key) => {
             // println!("{mode} {cipher_mode} {input} {key}");
             let flag = match mode.as_str() {
-                "-ecb" => rc5::Flags::ECB,
-                "-cbc" => rc5::Flags::CBC,
-                "-cbc_md5" => rc5::Flags::CBC_MD5,
-                _ => rc5::Flags::ECB,
+                "-ecb" => rc5::Flags::Ecb,
+                "-cbc" => rc5::Flags::Cbc,
+                "-cbc_md5" => rc5::Flags::CbcMd5,
+                _ => rc5::Flags::Ecb,
             };
 
             let rc5 = rc5::RC5::<u32>::new(12, 16, flag);
 
             match cipher_mode.as_str() {
                 "encrypt" => {
-                    let ciphertext = rc5.encrypt(input.as_bytes(), key.as_bytes());
+                    let ciphertext = rc5.encrypt(&input, key.as_bytes());
 
-                    print!("{}", String::from_utf8_lossy(&ciphertext[..]));
+                    io::stdout()
+                        .write_all(&ciphertext)
+                        .expect("Unable to write ciphertext");
                 }
                 "decrypt" => {
-                    let plaintext = rc5.decrypt(input.as_bytes(), key.as_bytes());
+                    let plaintext = rc5.decrypt(&input, key.as_bytes());
 
-                    print!("{}", String::from_utf8_lossy(&plaintext[..]));
+                    io::stdout()
+                        .write_all(&plaintext)
+                        .expect("Unable to write plaintext");
                 }
                 _ => {
                     panic!("Cannot handle a '{}' mode", cipher_mode);
                 }
             }
         }
-        Module::RSA(data) => {
+        Module::Rsa(data) => {
             let data = data.trim();
 
             let now = Instant::now();
             // 1. create rsa
             {
-                let mut rng = rand::thread_rng();
+                let mut rng = demo_rng(config.seed);
                 let bits = 2048;
 
                 // Create a pair of keys
@@ -416,7 +1443,7 @@ fn main() {
 
                 // Encrypt RSA
                 let enc_data = pub_key
-                    .encrypt(&mut rng, Pkcs1v15Encrypt, &data.as_bytes())
+                    .encrypt(&mut rng, Pkcs1v15Encrypt, data.as_bytes())
                     .expect("failed to encrypt");
                 assert_ne!(data.as_bytes(), &enc_data[..]);
 
@@ -434,7 +1461,7 @@ fn main() {
             let now = Instant::now();
 
             {
-                let mut rc5 = rc5::RC5::<u32>::new(12, 16, rc5::Flags::CBC);
+                let mut rc5 = rc5::RC5::<u32>::new(12, 16, rc5::Flags::Cbc);
 
                 let key = &[
                     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
@@ -452,9 +1479,9 @@ fn main() {
 
             // compare speed of encrypt and decrypt
         }
-        Module::DSA(mode, message) => match mode.as_str() {
+        Module::Dsa(mode, message) => match mode.as_str() {
             "generate" => {
-                let mut rng = rand::thread_rng();
+                let mut rng = demo_rng(config.seed);
                 let components = Components::generate(&mut rng, KeySize::DSA_2048_256);
                 let signing_key = SigningKey::generate(&mut rng, components);
                 let verifying_key = signing_key.verifying_key();
@@ -475,7 +1502,7 @@ fn main() {
                     .expect("Failed to decode PEM encoded key");
 
                 let signature = signing_key.sign_digest_with_rng(
-                    &mut rand::thread_rng(),
+                    &mut demo_rng(config.seed),
                     Sha1::new().chain_update(message.as_bytes()),
                 );
 
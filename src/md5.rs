@@ -1,43 +1,134 @@
 #![allow(non_snake_case)]
-static BUFFER: [u32; 4] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476];
+use crate::hash;
+use digest::consts::{U16, U64};
+use digest::core_api::BlockSizeUser;
+use digest::{FixedOutput, FixedOutputReset, HashMarker, Output, OutputSizeUser, Reset, Update};
+use std::io::{self, Read, Write};
+
+pub static BUFFER: [u32; 4] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476];
+
+/// T[i] = floor(2^32 * |sin(i + 1)|) of RFC 1321, written out rather than computed
+/// with `f64::sin` for every hash.
+pub const TABLE: [u32; 64] = [
+    0xD76AA478, 0xE8C7B756, 0x242070DB, 0xC1BDCEEE, 0xF57C0FAF, 0x4787C62A, 0xA8304613, 0xFD469501,
+    0x698098D8, 0x8B44F7AF, 0xFFFF5BB1, 0x895CD7BE, 0x6B901122, 0xFD987193, 0xA679438E, 0x49B40821,
+    0xF61E2562, 0xC040B340, 0x265E5A51, 0xE9B6C7AA, 0xD62F105D, 0x02441453, 0xD8A1E681, 0xE7D3FBC8,
+    0x21E1CDE6, 0xC33707D6, 0xF4D50D87, 0x455A14ED, 0xA9E3E905, 0xFCEFA3F8, 0x676F02D9, 0x8D2A4C8A,
+    0xFFFA3942, 0x8771F681, 0x6D9D6122, 0xFDE5380C, 0xA4BEEA44, 0x4BDECFA9, 0xF6BB4B60, 0xBEBFBC70,
+    0x289B7EC6, 0xEAA127FA, 0xD4EF3085, 0x04881D05, 0xD9D4D039, 0xE6DB99E5, 0x1FA27CF8, 0xC4AC5665,
+    0xF4292244, 0x432AFF97, 0xAB9423A7, 0xFC93A039, 0x655B59C3, 0x8F0CCC92, 0xFFEFF47D, 0x85845DD1,
+    0x6FA87E4F, 0xFE2CE6E0, 0xA3014314, 0x4E0811A1, 0xF7537E82, 0xBD3AF235, 0x2AD7D2BB, 0xEB86D391,
+];
+
+/// Bytes MD5 appends to a message of `length` bytes before the last block: the 1 bit,
+/// zeros up to 56 modulo 64, and the length in bits modulo 2^64.
+pub fn padding(length: u64) -> Vec<u8> {
+    // Step 1 Append Padding Bits, 128_u8 is the padding 1 bit followed by zeros
+    let zeros = (64 + 56 - (length as usize % 64 + 1) % 64) % 64;
+    let mut padding = vec![128_u8];
+    padding.resize(1 + zeros, 0);
+
+    // Step 2 Append Length (64 bit integer)
+    padding.extend_from_slice(&length.wrapping_mul(8).to_le_bytes());
+
+    padding
+}
+
+/// Incremental MD5: only the last partial 64 byte block is buffered, so input of any
+/// size can be fed in pieces.
+#[derive(Debug, Clone)]
+pub struct Md5 {
+    state: [u32; 4],
+    buffer: [u8; 64],
+    buffered: usize,
+    // total input length in bytes, MD5 appends it in bits modulo 2^64
+    length: u64,
+}
 
-fn elements_table() -> [u32; 64] {
-    let mut table = [0; 64];
-    for i in 1..=64 {
-        let x = i as f64;
-        let sin_eval = x.sin().abs();
-        table[i - 1] = ((2_u64.pow(32)) as f64 * sin_eval) as u32;
+impl Md5 {
+    pub fn new() -> Self {
+        Self {
+            state: BUFFER,
+            buffer: [0; 64],
+            buffered: 0,
+            length: 0,
+        }
     }
 
-    table
-}
+    /// Picks up hashing where the digest of `length` bytes left off, `length` counting the
+    /// padding too, so a multiple of 64. The digest is the whole state, which is why
+    /// MD5(secret || message) can be extended without knowing the secret.
+    pub fn resume(digest: Digest, length: u64) -> Self {
+        assert!(
+            length.is_multiple_of(64),
+            "MD5 resumes only on a block boundary, {} bytes is not one",
+            length
+        );
+
+        let mut state = [0; 4];
+        for (word, bytes) in state.iter_mut().zip(digest.0.chunks_exact(4)) {
+            *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
 
-pub struct MD5;
+        Self {
+            state,
+            length,
+            ..Self::new()
+        }
+    }
 
-impl MD5 {
-    pub fn from(input: &str) -> String {
-        // Step 1 Append Padding Bits
-        let mut raw_bytes = input.bytes().collect::<Vec<_>>();
+    pub fn update(&mut self, mut input: &[u8]) {
+        self.length = self.length.wrapping_add(input.len() as u64);
 
-        let bits_length = raw_bytes.len().saturating_mul(8) as u64;
+        // top up a partial block first
+        if self.buffered > 0 {
+            let take = input.len().min(64 - self.buffered);
+            self.buffer[self.buffered..self.buffered + take].copy_from_slice(&input[..take]);
+            self.buffered += take;
+            input = &input[take..];
+
+            if self.buffered < 64 {
+                return;
+            }
 
-        // 128_u8 is the equivalent of padding 1 as an unsigned 8-bit
-        raw_bytes.push(128_u8);
+            let block = self.buffer;
+            self.compress(&block);
+            self.buffered = 0;
+        }
 
-        while (raw_bytes.len() * 8) % 512 != 448 {
-            raw_bytes.push(0_u8);
+        let mut blocks = input.chunks_exact(64);
+        for block in blocks.by_ref() {
+            self.compress(block);
         }
 
-        // Step 2 Append Length  (64 bit integer)
-        for i in 0..8 {
-            raw_bytes.push((bits_length >> (i * 8)) as u8);
+        let rest = blocks.remainder();
+        self.buffer[..rest.len()].copy_from_slice(rest);
+        self.buffered = rest.len();
+    }
+
+    pub fn finalize(mut self) -> Digest {
+        // the length was counted already
+        let length = self.length;
+        self.update(&padding(length));
+        self.length = length;
+
+        let mut digest = [0; 16];
+        for (chunk, word) in digest.chunks_exact_mut(4).zip(self.state) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+
+        hash::Digest(digest)
+    }
+
+    // Steps 3 to 5 for a single 64 byte block
+    fn compress(&mut self, block: &[u8]) {
+        let mut X = [0_u32; 16];
+        for (word, bytes) in X.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
         }
 
         // Step 3 Initialize MD Buffer
-        let mut A = BUFFER[0];
-        let mut B = BUFFER[1];
-        let mut C = BUFFER[2];
-        let mut D = BUFFER[3];
+        let [mut A, mut B, mut C, mut D] = self.state;
 
         // Step 4 Process Message in 16-Word Blocks
         let F = |X: u32, Y: u32, Z: u32| -> u32 { X & Y | !X & Z };
@@ -45,144 +136,221 @@ impl MD5 {
         let H = |X: u32, Y: u32, Z: u32| -> u32 { X ^ Y ^ Z };
         let I = |X: u32, Y: u32, Z: u32| -> u32 { Y ^ (X | !Z) };
 
-        let table = elements_table();
+        let table = &TABLE;
+
+        // Step 5 Process the block
+        let AA = A;
+        let BB = B;
+        let CC = C;
+        let DD = D;
+
+        macro_rules! compress {
+            ($a:ident,$b:ident,$c:ident,$d:ident,$F:expr,$k:expr,$s:expr,$i:expr) => {
+                $a = $b.wrapping_add(
+                    ($a.wrapping_add($F($b, $c, $d))
+                        .wrapping_add(X[$k])
+                        .wrapping_add(table[$i]))
+                    .rotate_left($s),
+                )
+            };
+        }
 
-        // Step 5 Process blocks
-        for mut block in raw_bytes.chunks_exact_mut(64) {
-            let mut X = unsafe { std::mem::transmute::<&mut [u8], &mut [u32]>(block) };
-            #[cfg(target_endian = "big")]
-            for i in 0..16 {
-                X[i] = X[i].swap_bytes();
-            }
+        // Round 1. -> fF table[0..15], x[p1 * i]
+        // fH = (buffer[B] & buffer[C]) | (!buffer[B] & buffer[D])
+        // p1 = i
+        // shift = (i11 = 7, i12 = 12, i13 = 17, i14 = 22)
+
+        compress!(A, B, C, D, F, 0, 7, 0);
+        compress!(D, A, B, C, F, 1, 12, 1);
+        compress!(C, D, A, B, F, 2, 17, 2);
+        compress!(B, C, D, A, F, 3, 22, 3);
+
+        compress!(A, B, C, D, F, 4, 7, 4);
+        compress!(D, A, B, C, F, 5, 12, 5);
+        compress!(C, D, A, B, F, 6, 17, 6);
+        compress!(B, C, D, A, F, 7, 22, 7);
+
+        compress!(A, B, C, D, F, 8, 7, 8);
+        compress!(D, A, B, C, F, 9, 12, 9);
+        compress!(C, D, A, B, F, 10, 17, 10);
+        compress!(B, C, D, A, F, 11, 22, 11);
+
+        compress!(A, B, C, D, F, 12, 7, 12);
+        compress!(D, A, B, C, F, 13, 12, 13);
+        compress!(C, D, A, B, F, 14, 17, 14);
+        compress!(B, C, D, A, F, 15, 22, 15);
+
+        // Round 2. -> fG table[16..31], x[p2 * i]
+        // p2 = (1 + 5i) % 16
+        // shift = (i21 = 5, i22 = 9, i23 = 14, i24 = 20)
+        // fG = (buffer[B] & buffer[D]) | (buffer[C] & !buffer[D])
+
+        compress!(A, B, C, D, G, 1, 5, 16);
+        compress!(D, A, B, C, G, 6, 9, 17);
+        compress!(C, D, A, B, G, 11, 14, 18);
+        compress!(B, C, D, A, G, 0, 20, 19);
+
+        compress!(A, B, C, D, G, 5, 5, 20);
+        compress!(D, A, B, C, G, 10, 9, 21);
+        compress!(C, D, A, B, G, 15, 14, 22);
+        compress!(B, C, D, A, G, 4, 20, 23);
+
+        compress!(A, B, C, D, G, 9, 5, 24);
+        compress!(D, A, B, C, G, 14, 9, 25);
+        compress!(C, D, A, B, G, 3, 14, 26);
+        compress!(B, C, D, A, G, 8, 20, 27);
+
+        compress!(A, B, C, D, G, 13, 5, 28);
+        compress!(D, A, B, C, G, 2, 9, 29);
+        compress!(C, D, A, B, G, 7, 14, 30);
+        compress!(B, C, D, A, G, 12, 20, 31);
+
+        // Round 3. -> fH table[32..47], x[p3 * i]
+        // p3 = (5 + 3i) % 16
+        // shift = (i31 = 4, i32 = 11, i33 = 16, i34 = 23)
+        // fH = buffer[B] ^ buffer[C] ^ buffer[D]
+
+        compress!(A, B, C, D, H, 5, 4, 32);
+        compress!(D, A, B, C, H, 8, 11, 33);
+        compress!(C, D, A, B, H, 11, 16, 34);
+        compress!(B, C, D, A, H, 14, 23, 35);
+
+        compress!(A, B, C, D, H, 1, 4, 36);
+        compress!(D, A, B, C, H, 4, 11, 37);
+        compress!(C, D, A, B, H, 7, 16, 38);
+        compress!(B, C, D, A, H, 10, 23, 39);
+
+        compress!(A, B, C, D, H, 13, 4, 40);
+        compress!(D, A, B, C, H, 0, 11, 41);
+        compress!(C, D, A, B, H, 3, 16, 42);
+        compress!(B, C, D, A, H, 6, 23, 43);
+
+        compress!(A, B, C, D, H, 9, 4, 44);
+        compress!(D, A, B, C, H, 12, 11, 45);
+        compress!(C, D, A, B, H, 15, 16, 46);
+        compress!(B, C, D, A, H, 2, 23, 47);
+
+        // Round 4. -> fG table[48..63], x[p4 * i]
+        // p4 = 7i % 16
+        // shift = (i41 = 6, i42 = 10, i43 = 15, i44 = 21)
+        // fG = buffer[C] ^ (buffer[B] | !buffer[D])
+
+        compress!(A, B, C, D, I, 0, 6, 48);
+        compress!(D, A, B, C, I, 7, 10, 49);
+        compress!(C, D, A, B, I, 14, 15, 50);
+        compress!(B, C, D, A, I, 5, 21, 51);
+
+        compress!(A, B, C, D, I, 12, 6, 52);
+        compress!(D, A, B, C, I, 3, 10, 53);
+        compress!(C, D, A, B, I, 10, 15, 54);
+        compress!(B, C, D, A, I, 1, 21, 55);
+
+        compress!(A, B, C, D, I, 8, 6, 56);
+        compress!(D, A, B, C, I, 15, 10, 57);
+        compress!(C, D, A, B, I, 6, 15, 58);
+        compress!(B, C, D, A, I, 13, 21, 59);
+
+        compress!(A, B, C, D, I, 4, 6, 60);
+        compress!(D, A, B, C, I, 11, 10, 61);
+        compress!(C, D, A, B, I, 2, 15, 62);
+        compress!(B, C, D, A, I, 9, 21, 63);
+
+        A = A.wrapping_add(AA);
+        B = B.wrapping_add(BB);
+        C = C.wrapping_add(CC);
+        D = D.wrapping_add(DD);
+
+        self.state = [A, B, C, D];
+    }
+}
 
-            let AA = A;
-            let BB = B;
-            let CC = C;
-            let DD = D;
-
-            macro_rules! compress {
-                ($a:ident,$b:ident,$c:ident,$d:ident,$F:expr,$k:expr,$s:expr,$i:expr) => {
-                    $a = $b.wrapping_add(
-                        ($a.wrapping_add($F($b, $c, $d))
-                            .wrapping_add(X[$k])
-                            .wrapping_add(table[$i]))
-                        .rotate_left($s),
-                    )
-                };
-            }
+impl Default for Md5 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-            // Round 1. -> fF table[0..15], x[p1 * i]
-            // fH = (buffer[B] & buffer[C]) | (!buffer[B] & buffer[D])
-            // p1 = i
-            // shift = (i11 = 7, i12 = 12, i13 = 17, i14 = 22)
-
-            compress!(A, B, C, D, F, 0, 7, 0);
-            compress!(D, A, B, C, F, 1, 12, 1);
-            compress!(C, D, A, B, F, 2, 17, 2);
-            compress!(B, C, D, A, F, 3, 22, 3);
-
-            compress!(A, B, C, D, F, 4, 7, 4);
-            compress!(D, A, B, C, F, 5, 12, 5);
-            compress!(C, D, A, B, F, 6, 17, 6);
-            compress!(B, C, D, A, F, 7, 22, 7);
-
-            compress!(A, B, C, D, F, 8, 7, 8);
-            compress!(D, A, B, C, F, 9, 12, 9);
-            compress!(C, D, A, B, F, 10, 17, 10);
-            compress!(B, C, D, A, F, 11, 22, 11);
-
-            compress!(A, B, C, D, F, 12, 7, 12);
-            compress!(D, A, B, C, F, 13, 12, 13);
-            compress!(C, D, A, B, F, 14, 17, 14);
-            compress!(B, C, D, A, F, 15, 22, 15);
-
-            // Round 2. -> fG table[16..31], x[p2 * i]
-            // p2 = (1 + 5i) % 16
-            // shift = (i21 = 5, i22 = 9, i23 = 14, i24 = 20)
-            // fG = (buffer[B] & buffer[D]) | (buffer[C] & !buffer[D])
-
-            compress!(A, B, C, D, G, 1, 5, 16);
-            compress!(D, A, B, C, G, 6, 9, 17);
-            compress!(C, D, A, B, G, 11, 14, 18);
-            compress!(B, C, D, A, G, 0, 20, 19);
-
-            compress!(A, B, C, D, G, 5, 5, 20);
-            compress!(D, A, B, C, G, 10, 9, 21);
-            compress!(C, D, A, B, G, 15, 14, 22);
-            compress!(B, C, D, A, G, 4, 20, 23);
-
-            compress!(A, B, C, D, G, 9, 5, 24);
-            compress!(D, A, B, C, G, 14, 9, 25);
-            compress!(C, D, A, B, G, 3, 14, 26);
-            compress!(B, C, D, A, G, 8, 20, 27);
-
-            compress!(A, B, C, D, G, 13, 5, 28);
-            compress!(D, A, B, C, G, 2, 9, 29);
-            compress!(C, D, A, B, G, 7, 14, 30);
-            compress!(B, C, D, A, G, 12, 20, 31);
-
-            // Round 3. -> fH table[32..47], x[p3 * i]
-            // p3 = (5 + 3i) % 16
-            // shift = (i31 = 4, i32 = 11, i33 = 16, i34 = 23)
-            // fH = buffer[B] ^ buffer[C] ^ buffer[D]
-
-            compress!(A, B, C, D, H, 5, 4, 32);
-            compress!(D, A, B, C, H, 8, 11, 33);
-            compress!(C, D, A, B, H, 11, 16, 34);
-            compress!(B, C, D, A, H, 14, 23, 35);
-
-            compress!(A, B, C, D, H, 1, 4, 36);
-            compress!(D, A, B, C, H, 4, 11, 37);
-            compress!(C, D, A, B, H, 7, 16, 38);
-            compress!(B, C, D, A, H, 10, 23, 39);
-
-            compress!(A, B, C, D, H, 13, 4, 40);
-            compress!(D, A, B, C, H, 0, 11, 41);
-            compress!(C, D, A, B, H, 3, 16, 42);
-            compress!(B, C, D, A, H, 6, 23, 43);
-
-            compress!(A, B, C, D, H, 9, 4, 44);
-            compress!(D, A, B, C, H, 12, 11, 45);
-            compress!(C, D, A, B, H, 15, 16, 46);
-            compress!(B, C, D, A, H, 2, 23, 47);
-
-            // Round 4. -> fG table[48..63], x[p4 * i]
-            // p4 = 7i % 16
-            // shift = (i41 = 6, i42 = 10, i43 = 15, i44 = 21)
-            // fG = buffer[C] ^ (buffer[B] | !buffer[D])
-
-            compress!(A, B, C, D, I, 0, 6, 48);
-            compress!(D, A, B, C, I, 7, 10, 49);
-            compress!(C, D, A, B, I, 14, 15, 50);
-            compress!(B, C, D, A, I, 5, 21, 51);
-
-            compress!(A, B, C, D, I, 12, 6, 52);
-            compress!(D, A, B, C, I, 3, 10, 53);
-            compress!(C, D, A, B, I, 10, 15, 54);
-            compress!(B, C, D, A, I, 1, 21, 55);
-
-            compress!(A, B, C, D, I, 8, 6, 56);
-            compress!(D, A, B, C, I, 15, 10, 57);
-            compress!(C, D, A, B, I, 6, 15, 58);
-            compress!(B, C, D, A, I, 13, 21, 59);
-
-            compress!(A, B, C, D, I, 4, 6, 60);
-            compress!(D, A, B, C, I, 11, 10, 61);
-            compress!(C, D, A, B, I, 2, 15, 62);
-            compress!(B, C, D, A, I, 9, 21, 63);
-
-            A = A.wrapping_add(AA);
-            B = B.wrapping_add(BB);
-            C = C.wrapping_add(CC);
-            D = D.wrapping_add(DD);
-        }
+/// Lets `io::copy` stream a file or stdin into the hash.
+impl Write for Md5 {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// RustCrypto traits, with them `Md5` goes wherever `sha1::Sha1` does, e.g. DSA signing.
+// `digest::Digest` itself comes from the blanket implementation over these.
+
+impl HashMarker for Md5 {}
+
+impl OutputSizeUser for Md5 {
+    type OutputSize = U16;
+}
+
+impl BlockSizeUser for Md5 {
+    type BlockSize = U64;
+}
+
+impl Update for Md5 {
+    fn update(&mut self, data: &[u8]) {
+        Md5::update(self, data);
+    }
+}
+
+impl FixedOutput for Md5 {
+    fn finalize_into(self, out: &mut Output<Self>) {
+        out.copy_from_slice(&Md5::finalize(self).0);
+    }
+}
+
+impl Reset for Md5 {
+    fn reset(&mut self) {
+        *self = Md5::new();
+    }
+}
+
+impl FixedOutputReset for Md5 {
+    fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+        out.copy_from_slice(&Md5::finalize(std::mem::take(self)).0);
+    }
+}
+
+/// 128 bit MD5 value.
+pub type Digest = crate::hash::Digest<16>;
+
+pub struct MD5;
+
+impl MD5 {
+    pub fn digest(input: &[u8]) -> Digest {
+        let mut md5 = Md5::new();
+        md5.update(input);
+
+        md5.finalize()
+    }
+
+    /// Digest of everything `reader` yields, read in chunks in constant memory.
+    pub fn digest_reader<R: Read>(mut reader: R) -> io::Result<Digest> {
+        let mut md5 = Md5::new();
+        io::copy(&mut reader, &mut md5)?;
+
+        Ok(md5.finalize())
+    }
+
+    /// Uppercase hex of the digest, for printing.
+    pub fn from(input: &str) -> String {
+        Self::from_bytes(input.as_bytes())
+    }
+
+    /// Any bytes, UTF-8 or not.
+    pub fn from_bytes(input: &[u8]) -> String {
+        Self::digest(input).to_upper_hex()
+    }
 
-        format!(
-            "{:08X}{:08X}{:08X}{:08X}",
-            A.swap_bytes(),
-            B.swap_bytes(),
-            C.swap_bytes(),
-            D.swap_bytes()
-        )
+    pub fn from_reader<R: Read>(reader: R) -> io::Result<String> {
+        Ok(Self::digest_reader(reader)?.to_upper_hex())
     }
 }
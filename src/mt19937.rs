@@ -0,0 +1,93 @@
+use crate::generator::{fill_bytes, Generator};
+use rand::RngCore;
+
+const N: usize = 624;
+const M: usize = 397;
+const MATRIX_A: u32 = 0x9908b0df;
+const UPPER_MASK: u32 = 0x8000_0000;
+const LOWER_MASK: u32 = 0x7fff_ffff;
+
+/// Matsumoto and Nishimura's 32-bit Mersenne Twister, period 2^19937 - 1.
+#[derive(Debug, Clone)]
+pub struct MT19937 {
+    state: [u32; N],
+    index: usize,
+}
+
+impl MT19937 {
+    /// `init_genrand` of the reference implementation, its default seed is 5489.
+    pub fn new(seed: u32) -> Self {
+        let mut state = [0; N];
+        state[0] = seed;
+
+        for i in 1..N {
+            let previous = state[i - 1];
+            state[i] = 1812433253_u32
+                .wrapping_mul(previous ^ (previous >> 30))
+                .wrapping_add(i as u32);
+        }
+
+        Self { state, index: N }
+    }
+
+    fn output(&mut self) -> u32 {
+        if self.index >= N {
+            self.twist();
+        }
+
+        let mut y = self.state[self.index];
+        self.index += 1;
+
+        // tempering
+        y ^= y >> 11;
+        y ^= (y << 7) & 0x9d2c5680;
+        y ^= (y << 15) & 0xefc60000;
+        y ^ (y >> 18)
+    }
+
+    fn twist(&mut self) {
+        for i in 0..N {
+            let y = (self.state[i] & UPPER_MASK) | (self.state[(i + 1) % N] & LOWER_MASK);
+            let mag = if y & 1 == 1 { MATRIX_A } else { 0 };
+
+            self.state[i] = self.state[(i + M) % N] ^ (y >> 1) ^ mag;
+        }
+
+        self.index = 0;
+    }
+}
+
+impl RngCore for MT19937 {
+    fn next_u32(&mut self) -> u32 {
+        self.output()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let low = self.output() as u64;
+
+        ((self.output() as u64) << 32) | low
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        fill_bytes(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl Generator for MT19937 {
+    fn modulus(&self) -> Option<u64> {
+        Some(1 << 32)
+    }
+}
+
+impl Iterator for MT19937 {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.output() as u64)
+    }
+}
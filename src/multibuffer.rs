@@ -0,0 +1,499 @@
+use crate::hash;
+use crate::md5::{padding, Digest, Md5, BUFFER, TABLE};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
+/// Widest engine, the state and message words of every lane are kept this wide.
+pub const MAX_LANES: usize = 8;
+
+const SHIFTS: [[u32; 4]; 4] = [
+    [7, 12, 17, 22],
+    [5, 9, 14, 20],
+    [4, 11, 16, 23],
+    [6, 10, 15, 21],
+];
+
+// A, B, C, D of every lane
+type State = [[u32; MAX_LANES]; 4];
+// the 16 message words of the current block of every lane
+type Words = [[u32; MAX_LANES]; 16];
+
+/// How many independent messages go through the MD5 rounds side by side. Multi-buffer
+/// pays off for many inputs, a single message is as fast with `Md5`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Engine {
+    // 8 lanes in 256 bit registers
+    Avx2,
+    // 4 lanes in 128 bit registers
+    Sse2,
+    // 8 lanes in arrays, left to the compiler to vectorize, any target
+    Portable,
+}
+
+impl Engine {
+    /// The widest engine this CPU runs.
+    pub fn detect() -> Self {
+        Self::available()[0]
+    }
+
+    /// Every engine this CPU runs, widest first.
+    pub fn available() -> Vec<Engine> {
+        [Engine::Avx2, Engine::Sse2, Engine::Portable]
+            .into_iter()
+            .filter(|engine| engine.is_supported())
+            .collect()
+    }
+
+    pub fn is_supported(self) -> bool {
+        match self {
+            #[cfg(target_arch = "x86_64")]
+            Engine::Avx2 => is_x86_feature_detected!("avx2"),
+            #[cfg(target_arch = "x86_64")]
+            Engine::Sse2 => is_x86_feature_detected!("sse2"),
+            Engine::Portable => true,
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+
+    pub fn lanes(self) -> usize {
+        match self {
+            Engine::Avx2 | Engine::Portable => 8,
+            Engine::Sse2 => 4,
+        }
+    }
+
+    /// Digests of all the inputs, in order. Panics when the CPU lacks the instructions.
+    pub fn digest_many(self, inputs: &[&[u8]]) -> Vec<Digest> {
+        assert!(self.is_supported(), "{} is not supported by this CPU", self);
+
+        let compress: fn(&mut State, &Words) = match self {
+            #[cfg(target_arch = "x86_64")]
+            // SAFETY: is_supported checked the CPU has AVX2
+            Engine::Avx2 => |state, words| unsafe { compress_avx2(state, words) },
+            #[cfg(target_arch = "x86_64")]
+            // SAFETY: is_supported checked the CPU has SSE2
+            Engine::Sse2 => |state, words| unsafe { compress_sse2(state, words) },
+            _ => compress_portable,
+        };
+
+        schedule(inputs, self.lanes(), compress)
+    }
+}
+
+impl fmt::Display for Engine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Engine::Avx2 => write!(f, "AVX2"),
+            Engine::Sse2 => write!(f, "SSE2"),
+            Engine::Portable => write!(f, "portable"),
+        }
+    }
+}
+
+/// Digests of all the inputs with the widest engine of this CPU.
+pub fn digest_many(inputs: &[&[u8]]) -> Vec<Digest> {
+    Engine::detect().digest_many(inputs)
+}
+
+// One message in a lane: its whole blocks are read in place, only the last partial block
+// and the padding are copied.
+struct Job<'a> {
+    index: usize,
+    input: &'a [u8],
+    // the last partial block and the padding fill one or two blocks
+    tail: [u8; 128],
+    tail_length: usize,
+    block: usize,
+}
+
+impl<'a> Job<'a> {
+    fn new(index: usize, input: &'a [u8]) -> Self {
+        let whole = input.len() / 64 * 64;
+        let padding = padding(input.len() as u64);
+        let rest = input.len() - whole;
+
+        let mut tail = [0; 128];
+        tail[..rest].copy_from_slice(&input[whole..]);
+        tail[rest..rest + padding.len()].copy_from_slice(&padding);
+
+        Self {
+            index,
+            input: &input[..whole],
+            tail,
+            tail_length: rest + padding.len(),
+            block: 0,
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.block * 64 == self.input.len() + self.tail_length
+    }
+
+    fn next_block(&mut self) -> &[u8] {
+        let offset = self.block * 64;
+        self.block += 1;
+
+        if offset < self.input.len() {
+            &self.input[offset..offset + 64]
+        } else {
+            &self.tail[offset - self.input.len()..offset - self.input.len() + 64]
+        }
+    }
+}
+
+// A lane whose message is done takes the next one right away, so inputs of different
+// lengths keep every lane busy until the queue runs dry.
+fn schedule(inputs: &[&[u8]], lanes: usize, compress: fn(&mut State, &Words)) -> Vec<Digest> {
+    let mut digests = vec![hash::Digest([0; 16]); inputs.len()];
+    let mut queue = inputs.iter().enumerate();
+
+    let mut jobs = Vec::with_capacity(lanes);
+    let mut state = [[0; MAX_LANES]; 4];
+    let mut words = [[0; MAX_LANES]; 16];
+
+    for lane in 0..lanes {
+        jobs.push(queue.next().map(|(index, input)| Job::new(index, input)));
+
+        for (word, initial) in state.iter_mut().zip(BUFFER) {
+            word[lane] = initial;
+        }
+    }
+
+    loop {
+        let mut busy = false;
+
+        for (lane, slot) in jobs.iter_mut().enumerate() {
+            // a finished lane hands its digest over and starts the next message
+            while let Some(job) = slot.as_ref().filter(|job| job.is_done()) {
+                let mut digest = [0; 16];
+                for (bytes, word) in digest.chunks_exact_mut(4).zip(&state) {
+                    bytes.copy_from_slice(&word[lane].to_le_bytes());
+                }
+                digests[job.index] = hash::Digest(digest);
+
+                *slot = queue.next().map(|(index, input)| Job::new(index, input));
+                for (word, initial) in state.iter_mut().zip(BUFFER) {
+                    word[lane] = initial;
+                }
+            }
+
+            // an idle lane keeps its last words, what it computes is never read
+            if let Some(job) = slot {
+                busy = true;
+
+                let block = job.next_block();
+                for (word, bytes) in words.iter_mut().zip(block.chunks_exact(4)) {
+                    word[lane] = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                }
+            }
+        }
+
+        if !busy {
+            return digests;
+        }
+
+        compress(&mut state, &words);
+    }
+}
+
+// The operations the rounds need on a register of lanes.
+//
+// Safety: the SIMD implementations run instructions the CPU may lack, every method may
+// only be called once the CPU was checked for them, as `Engine::is_supported` does before
+// `compress_sse2` and `compress_avx2`.
+trait Lanes: Copy {
+    unsafe fn load(words: &[u32; MAX_LANES]) -> Self;
+    unsafe fn store(self, words: &mut [u32; MAX_LANES]);
+    unsafe fn splat(value: u32) -> Self;
+    unsafe fn add(self, other: Self) -> Self;
+    unsafe fn and(self, other: Self) -> Self;
+    // !self & other
+    unsafe fn andnot(self, other: Self) -> Self;
+    unsafe fn or(self, other: Self) -> Self;
+    unsafe fn xor(self, other: Self) -> Self;
+    unsafe fn rotate_left(self, shift: u32) -> Self;
+}
+
+// RFC 1321 steps 3 to 5 on every lane at once, the same rounds as `Md5::compress`.
+// Safety: as for the `Lanes` methods of `V`.
+#[inline(always)]
+unsafe fn rounds<V: Lanes>(state: &mut State, words: &Words) {
+    let x = words.map(|word| V::load(&word));
+    let [mut a, mut b, mut c, mut d] = state.map(|word| V::load(&word));
+    let ones = V::splat(u32::MAX);
+
+    for i in 0..64 {
+        let (f, k) = match i / 16 {
+            0 => (b.and(c).or(b.andnot(d)), i),
+            1 => (b.and(d).or(d.andnot(c)), (5 * i + 1) % 16),
+            2 => (b.xor(c).xor(d), (3 * i + 5) % 16),
+            _ => (c.xor(b.or(d.xor(ones))), (7 * i) % 16),
+        };
+
+        let rotated = a
+            .add(f)
+            .add(V::splat(TABLE[i]))
+            .add(x[k])
+            .rotate_left(SHIFTS[i / 16][i % 4]);
+
+        (a, b, c, d) = (d, b.add(rotated), b, c);
+    }
+
+    for (word, value) in state.iter_mut().zip([a, b, c, d]) {
+        V::load(word).add(value).store(word);
+    }
+}
+
+impl Lanes for [u32; MAX_LANES] {
+    #[inline(always)]
+    unsafe fn load(words: &[u32; MAX_LANES]) -> Self {
+        *words
+    }
+
+    #[inline(always)]
+    unsafe fn store(self, words: &mut [u32; MAX_LANES]) {
+        *words = self;
+    }
+
+    #[inline(always)]
+    unsafe fn splat(value: u32) -> Self {
+        [value; MAX_LANES]
+    }
+
+    #[inline(always)]
+    unsafe fn add(self, other: Self) -> Self {
+        std::array::from_fn(|i| self[i].wrapping_add(other[i]))
+    }
+
+    #[inline(always)]
+    unsafe fn and(self, other: Self) -> Self {
+        std::array::from_fn(|i| self[i] & other[i])
+    }
+
+    #[inline(always)]
+    unsafe fn andnot(self, other: Self) -> Self {
+        std::array::from_fn(|i| !self[i] & other[i])
+    }
+
+    #[inline(always)]
+    unsafe fn or(self, other: Self) -> Self {
+        std::array::from_fn(|i| self[i] | other[i])
+    }
+
+    #[inline(always)]
+    unsafe fn xor(self, other: Self) -> Self {
+        std::array::from_fn(|i| self[i] ^ other[i])
+    }
+
+    #[inline(always)]
+    unsafe fn rotate_left(self, shift: u32) -> Self {
+        self.map(|word| word.rotate_left(shift))
+    }
+}
+
+fn compress_portable(state: &mut State, words: &Words) {
+    // SAFETY: arrays of lanes are plain Rust, any CPU runs them
+    unsafe { rounds::<[u32; MAX_LANES]>(state, words) }
+}
+
+#[cfg(target_arch = "x86_64")]
+impl Lanes for __m128i {
+    #[inline(always)]
+    unsafe fn load(words: &[u32; MAX_LANES]) -> Self {
+        unsafe { _mm_loadu_si128(words.as_ptr() as *const __m128i) }
+    }
+
+    #[inline(always)]
+    unsafe fn store(self, words: &mut [u32; MAX_LANES]) {
+        unsafe { _mm_storeu_si128(words.as_mut_ptr() as *mut __m128i, self) }
+    }
+
+    #[inline(always)]
+    unsafe fn splat(value: u32) -> Self {
+        unsafe { _mm_set1_epi32(value as i32) }
+    }
+
+    #[inline(always)]
+    unsafe fn add(self, other: Self) -> Self {
+        unsafe { _mm_add_epi32(self, other) }
+    }
+
+    #[inline(always)]
+    unsafe fn and(self, other: Self) -> Self {
+        unsafe { _mm_and_si128(self, other) }
+    }
+
+    #[inline(always)]
+    unsafe fn andnot(self, other: Self) -> Self {
+        unsafe { _mm_andnot_si128(self, other) }
+    }
+
+    #[inline(always)]
+    unsafe fn or(self, other: Self) -> Self {
+        unsafe { _mm_or_si128(self, other) }
+    }
+
+    #[inline(always)]
+    unsafe fn xor(self, other: Self) -> Self {
+        unsafe { _mm_xor_si128(self, other) }
+    }
+
+    #[inline(always)]
+    unsafe fn rotate_left(self, shift: u32) -> Self {
+        unsafe {
+            let left = _mm_sll_epi32(self, _mm_cvtsi32_si128(shift as i32));
+            let right = _mm_srl_epi32(self, _mm_cvtsi32_si128(32 - shift as i32));
+
+            _mm_or_si128(left, right)
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+impl Lanes for __m256i {
+    #[inline(always)]
+    unsafe fn load(words: &[u32; MAX_LANES]) -> Self {
+        unsafe { _mm256_loadu_si256(words.as_ptr() as *const __m256i) }
+    }
+
+    #[inline(always)]
+    unsafe fn store(self, words: &mut [u32; MAX_LANES]) {
+        unsafe { _mm256_storeu_si256(words.as_mut_ptr() as *mut __m256i, self) }
+    }
+
+    #[inline(always)]
+    unsafe fn splat(value: u32) -> Self {
+        unsafe { _mm256_set1_epi32(value as i32) }
+    }
+
+    #[inline(always)]
+    unsafe fn add(self, other: Self) -> Self {
+        unsafe { _mm256_add_epi32(self, other) }
+    }
+
+    #[inline(always)]
+    unsafe fn and(self, other: Self) -> Self {
+        unsafe { _mm256_and_si256(self, other) }
+    }
+
+    #[inline(always)]
+    unsafe fn andnot(self, other: Self) -> Self {
+        unsafe { _mm256_andnot_si256(self, other) }
+    }
+
+    #[inline(always)]
+    unsafe fn or(self, other: Self) -> Self {
+        unsafe { _mm256_or_si256(self, other) }
+    }
+
+    #[inline(always)]
+    unsafe fn xor(self, other: Self) -> Self {
+        unsafe { _mm256_xor_si256(self, other) }
+    }
+
+    #[inline(always)]
+    unsafe fn rotate_left(self, shift: u32) -> Self {
+        unsafe {
+            let left = _mm256_sll_epi32(self, _mm_cvtsi32_si128(shift as i32));
+            let right = _mm256_srl_epi32(self, _mm_cvtsi32_si128(32 - shift as i32));
+
+            _mm256_or_si256(left, right)
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "sse2")]
+fn compress_sse2(state: &mut State, words: &Words) {
+    // SAFETY: this function is only called on CPUs with SSE2
+    unsafe { rounds::<__m128i>(state, words) }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+fn compress_avx2(state: &mut State, words: &Words) {
+    // SAFETY: this function is only called on CPUs with AVX2
+    unsafe { rounds::<__m256i>(state, words) }
+}
+
+/// Bytes hashed by one engine in the time it took.
+#[derive(Debug, Clone, Copy)]
+pub struct Throughput {
+    // None for one `Md5` after the other
+    pub engine: Option<Engine>,
+    pub bytes: u64,
+    pub elapsed: Duration,
+}
+
+impl Throughput {
+    pub fn megabytes_per_second(&self) -> f64 {
+        self.bytes as f64 / 1e6 / self.elapsed.as_secs_f64()
+    }
+}
+
+impl fmt::Display for Throughput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self.engine {
+            Some(engine) => format!("{} x{}", engine, engine.lanes()),
+            None => String::from("scalar"),
+        };
+
+        write!(
+            f,
+            "{:<12} {:>10.1} MB/s {:>10.2?}",
+            name,
+            self.megabytes_per_second(),
+            self.elapsed
+        )
+    }
+}
+
+/// Hashes `count` messages of `size` bytes one by one and with every engine of this
+/// CPU, checking the engines agree with the scalar digests.
+pub fn benchmark(size: usize, count: usize) -> Vec<Throughput> {
+    let messages = (0..count)
+        .map(|i| (0..size).map(|j| (i * 31 + j) as u8).collect::<Vec<u8>>())
+        .collect::<Vec<_>>();
+    let inputs = messages.iter().map(Vec::as_slice).collect::<Vec<_>>();
+    let bytes = (size * count) as u64;
+
+    let now = Instant::now();
+    let expected = inputs
+        .iter()
+        .map(|input| {
+            let mut md5 = Md5::new();
+            md5.update(input);
+            md5.finalize()
+        })
+        .collect::<Vec<_>>();
+
+    let mut report = vec![Throughput {
+        engine: None,
+        bytes,
+        elapsed: now.elapsed(),
+    }];
+
+    for engine in Engine::available() {
+        let now = Instant::now();
+        let digests = engine.digest_many(&inputs);
+        let elapsed = now.elapsed();
+
+        assert_eq!(
+            digests, expected,
+            "{} disagrees with the scalar MD5",
+            engine
+        );
+
+        report.push(Throughput {
+            engine: Some(engine),
+            bytes,
+            elapsed,
+        });
+    }
+
+    report
+}
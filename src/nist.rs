@@ -0,0 +1,732 @@
+use crate::stats::{erfc, igamc, normal_cdf, TestResult};
+use num::complex::Complex64;
+use std::f64::consts::{PI, SQRT_2};
+
+/// Unpacks bytes into bits, most significant bit first, one bit per `u8`.
+pub fn bits_from_bytes(bytes: &[u8]) -> Vec<u8> {
+    bytes
+        .iter()
+        .flat_map(|byte| (0..8).rev().map(move |i| (byte >> i) & 1))
+        .collect()
+}
+
+/// Runs every test that has enough input with the parameters recommended by
+/// SP 800-22, tests whose preconditions are not met are left out.
+pub fn suite(bits: &[u8]) -> Vec<TestResult> {
+    let n = bits.len();
+    let log = (usize::BITS - 1 - n.max(1).leading_zeros()) as usize;
+
+    let mut results = Vec::new();
+
+    if n >= 100 {
+        results.push(monobit(bits));
+        results.push(runs(bits));
+    }
+
+    if n >= 128 {
+        results.push(block_frequency(bits, 128));
+        results.push(longest_run(bits));
+    }
+
+    if n >= 38 * 32 * 32 {
+        results.push(rank(bits));
+    }
+
+    if n >= 1000 {
+        results.push(dft(bits));
+    }
+
+    // one expected match of the 9 bit template in each of the 8 blocks
+    if n >= 8 * 512 {
+        results.push(non_overlapping_template(
+            bits,
+            &[0, 0, 0, 0, 0, 0, 0, 0, 1],
+            8,
+        ));
+    }
+
+    if n >= 1032 * 100 {
+        results.push(overlapping_template(bits));
+    }
+
+    if let Some(l) = UNIVERSAL.iter().rposition(|row| n >= row.0) {
+        results.push(universal(bits, l + 6, 10 << (l + 6)));
+    }
+
+    if n >= 500 * 200 {
+        results.push(linear_complexity(bits, 500));
+    }
+
+    if log >= 5 {
+        results.extend(serial(bits, (log - 3).min(16)));
+    }
+
+    if log >= 7 {
+        results.push(approximate_entropy(bits, (log - 6).min(10)));
+    }
+
+    if n >= 100 {
+        results.extend(cumulative_sums(bits));
+    }
+
+    // both excursion tests need at least 500 cycles of the random walk
+    if zero_crossings(bits) >= 500 {
+        results.extend(random_excursions(bits));
+        results.extend(random_excursions_variant(bits));
+    }
+
+    results
+}
+
+pub fn monobit(bits: &[u8]) -> TestResult {
+    let n = bits.len() as f64;
+    let sum = bits.iter().map(|b| 2 * *b as i64 - 1).sum::<i64>();
+    let statistic = sum.abs() as f64 / n.sqrt();
+
+    TestResult::new("Monobit", statistic, erfc(statistic / SQRT_2))
+}
+
+pub fn block_frequency(bits: &[u8], m: usize) -> TestResult {
+    let blocks = bits.len() / m;
+
+    let statistic = 4.0
+        * m as f64
+        * bits
+            .chunks_exact(m)
+            .map(|block| (ones(block) as f64 / m as f64 - 0.5).powi(2))
+            .sum::<f64>();
+
+    TestResult::new(
+        "Block frequency",
+        statistic,
+        igamc(blocks as f64 / 2.0, statistic / 2.0),
+    )
+}
+
+pub fn runs(bits: &[u8]) -> TestResult {
+    let n = bits.len() as f64;
+    let pi = ones(bits) as f64 / n;
+
+    // frequency prerequisite, the runs test is meaningless for a biased sequence
+    if (pi - 0.5).abs() >= 2.0 / n.sqrt() {
+        return TestResult::new("Runs", 0.0, 0.0);
+    }
+
+    let statistic = 1 + bits.windows(2).filter(|w| w[0] != w[1]).count();
+    let p_value = erfc(
+        (statistic as f64 - 2.0 * n * pi * (1.0 - pi)).abs()
+            / (2.0 * (2.0 * n).sqrt() * pi * (1.0 - pi)),
+    );
+
+    TestResult::new("Runs", statistic as f64, p_value)
+}
+
+pub fn longest_run(bits: &[u8]) -> TestResult {
+    let n = bits.len();
+
+    // block length, shortest counted run and class probabilities by input size
+    let (m, shortest, probabilities): (usize, usize, &[f64]) = if n < 6272 {
+        (8, 1, &[0.2148, 0.3672, 0.2305, 0.1875])
+    } else if n < 750_000 {
+        (128, 4, &[0.1174, 0.2430, 0.2493, 0.1752, 0.1027, 0.1124])
+    } else {
+        (
+            10_000,
+            10,
+            &[0.0882, 0.2092, 0.2483, 0.1933, 0.1208, 0.0675, 0.0727],
+        )
+    };
+
+    let mut observed = vec![0; probabilities.len()];
+
+    for block in bits.chunks_exact(m) {
+        let mut longest = 0;
+        let mut current = 0;
+
+        for bit in block {
+            current = if *bit == 1 { current + 1 } else { 0 };
+            longest = longest.max(current);
+        }
+
+        let class = longest.clamp(shortest, shortest + probabilities.len() - 1) - shortest;
+        observed[class] += 1;
+    }
+
+    let blocks = (n / m) as f64;
+    let statistic = chi_statistic(&observed, probabilities, blocks);
+    let freedom = (probabilities.len() - 1) as f64;
+
+    TestResult::new(
+        "Longest run of ones",
+        statistic,
+        igamc(freedom / 2.0, statistic / 2.0),
+    )
+}
+
+// Ranks of disjoint 32x32 matrices over GF(2)
+pub fn rank(bits: &[u8]) -> TestResult {
+    let mut observed = [0; 3];
+
+    for block in bits.chunks_exact(32 * 32) {
+        let mut rows = block
+            .chunks_exact(32)
+            .map(|row| row.iter().fold(0_u32, |acc, bit| (acc << 1) | *bit as u32))
+            .collect::<Vec<_>>();
+
+        match gf2_rank(&mut rows) {
+            32 => observed[0] += 1,
+            31 => observed[1] += 1,
+            _ => observed[2] += 1,
+        }
+    }
+
+    let blocks = observed.iter().sum::<u64>() as f64;
+    let statistic = chi_statistic(&observed, &[0.288788, 0.577576, 0.133636], blocks);
+
+    TestResult::new("Binary matrix rank", statistic, (-statistic / 2.0).exp())
+}
+
+// Spectral test: peaks of the DFT above the 95% threshold
+pub fn dft(bits: &[u8]) -> TestResult {
+    let n = bits.len();
+    let signal = bits
+        .iter()
+        .map(|bit| Complex64::new(2.0 * *bit as f64 - 1.0, 0.0))
+        .collect::<Vec<_>>();
+
+    let spectrum = bluestein(&signal);
+    let threshold = ((1.0 / 0.05_f64).ln() * n as f64).sqrt();
+
+    let expected = 0.95 * n as f64 / 2.0;
+    let below = spectrum[..n / 2]
+        .iter()
+        .filter(|s| s.norm() < threshold)
+        .count() as f64;
+
+    let statistic = (below - expected) / (n as f64 * 0.95 * 0.05 / 4.0).sqrt();
+
+    TestResult::new(
+        "Discrete Fourier transform",
+        statistic,
+        erfc(statistic.abs() / SQRT_2),
+    )
+}
+
+// Occurrences of an aperiodic template in `blocks` blocks, the window skips past
+// every match
+pub fn non_overlapping_template(bits: &[u8], template: &[u8], blocks: usize) -> TestResult {
+    let m = template.len();
+    let block = bits.len() / blocks;
+
+    let observed = bits
+        .chunks_exact(block)
+        .take(blocks)
+        .map(|chunk| {
+            let mut count = 0;
+            let mut i = 0;
+
+            while i + m <= block {
+                if &chunk[i..i + m] == template {
+                    count += 1;
+                    i += m;
+                } else {
+                    i += 1;
+                }
+            }
+
+            count as f64
+        })
+        .collect::<Vec<_>>();
+
+    let mean = (block - m + 1) as f64 / 2_f64.powi(m as i32);
+    let variance =
+        block as f64 * (1.0 / 2_f64.powi(m as i32) - (2 * m - 1) as f64 / 2_f64.powi(2 * m as i32));
+
+    let statistic = observed
+        .iter()
+        .map(|w| (w - mean).powi(2) / variance)
+        .sum::<f64>();
+
+    TestResult::new(
+        "Non-overlapping template",
+        statistic,
+        igamc(blocks as f64 / 2.0, statistic / 2.0),
+    )
+}
+
+// Overlapping occurrences of 9 ones in blocks of 1032 bits. The class probabilities are
+// the corrected ones of revision 1a, the formula of the original text overestimates the
+// first class
+pub fn overlapping_template(bits: &[u8]) -> TestResult {
+    const BLOCK: usize = 1032;
+    const TEMPLATE: usize = 9;
+    const PROBABILITIES: [f64; 6] = [0.364091, 0.185659, 0.139381, 0.100571, 0.070432, 0.139865];
+
+    let classes = PROBABILITIES.len() - 1;
+    let mut observed = [0_u64; 6];
+
+    for chunk in bits.chunks_exact(BLOCK) {
+        let count = chunk
+            .windows(TEMPLATE)
+            .filter(|w| w.iter().all(|b| *b == 1))
+            .count();
+        observed[count.min(classes)] += 1;
+    }
+
+    let blocks = (bits.len() / BLOCK) as f64;
+    let statistic = chi_statistic(&observed, &PROBABILITIES, blocks);
+
+    TestResult::new(
+        "Overlapping template",
+        statistic,
+        igamc(classes as f64 / 2.0, statistic / 2.0),
+    )
+}
+
+// Smallest input size, expected value and variance of the statistic for L = 6..16
+const UNIVERSAL: [(usize, f64, f64); 11] = [
+    (387_840, 5.2177052, 2.954),
+    (904_960, 6.1962507, 3.125),
+    (2_068_480, 7.1836656, 3.238),
+    (4_654_080, 8.1764248, 3.311),
+    (10_342_400, 9.1723243, 3.356),
+    (22_753_280, 10.170032, 3.384),
+    (49_643_520, 11.168765, 3.401),
+    (107_560_960, 12.168070, 3.410),
+    (231_669_760, 13.167693, 3.416),
+    (496_435_200, 14.167488, 3.419),
+    (1_059_061_760, 15.167379, 3.421),
+];
+
+// Expected value and variance of the statistic for L = 1..5
+const UNIVERSAL_SMALL: [(f64, f64); 5] = [
+    (0.7326495, 0.690),
+    (1.5374383, 1.338),
+    (2.4016068, 1.901),
+    (3.3112247, 2.358),
+    (4.2534266, 2.705),
+];
+
+// Maurer's universal statistical test with L-bit words and Q initialization blocks
+pub fn universal(bits: &[u8], l: usize, q: usize) -> TestResult {
+    let (expected, variance) = if l <= 5 {
+        UNIVERSAL_SMALL[l - 1]
+    } else {
+        let row = UNIVERSAL[l - 6];
+        (row.1, row.2)
+    };
+
+    let words = bits
+        .chunks_exact(l)
+        .map(|word| {
+            word.iter()
+                .fold(0_usize, |acc, bit| (acc << 1) | *bit as usize)
+        })
+        .collect::<Vec<_>>();
+
+    let k = words.len() - q;
+    let mut last_seen = vec![0_usize; 1 << l];
+
+    for (i, word) in words[..q].iter().enumerate() {
+        last_seen[*word] = i + 1;
+    }
+
+    let mut sum = 0.0;
+
+    for (i, word) in words[q..].iter().enumerate() {
+        let position = q + i + 1;
+        sum += ((position - last_seen[*word]) as f64).log2();
+        last_seen[*word] = position;
+    }
+
+    let statistic = sum / k as f64;
+    let c =
+        0.7 - 0.8 / l as f64 + (4.0 + 32.0 / l as f64) * (k as f64).powf(-3.0 / l as f64) / 15.0;
+    let sigma = c * (variance / k as f64).sqrt();
+
+    TestResult::new(
+        "Maurer universal",
+        statistic,
+        erfc((statistic - expected).abs() / (SQRT_2 * sigma)),
+    )
+}
+
+pub fn linear_complexity(bits: &[u8], m: usize) -> TestResult {
+    let probabilities = [0.010417, 0.03125, 0.125, 0.5, 0.25, 0.0625, 0.020833];
+    let sign = if m.is_multiple_of(2) { 1.0 } else { -1.0 };
+
+    let mean =
+        m as f64 / 2.0 + (9.0 - sign) / 36.0 - (m as f64 / 3.0 + 2.0 / 9.0) / 2_f64.powi(m as i32);
+
+    let mut observed = [0_u64; 7];
+
+    for block in bits.chunks_exact(m) {
+        let t = sign * (berlekamp_massey(block) as f64 - mean) + 2.0 / 9.0;
+
+        // classes are split at -2.5, -1.5, ..., 2.5
+        let class = ((t + 3.5).ceil() - 1.0).clamp(0.0, 6.0) as usize;
+        observed[class] += 1;
+    }
+
+    let blocks = (bits.len() / m) as f64;
+    let statistic = chi_statistic(&observed, &probabilities, blocks);
+
+    TestResult::new("Linear complexity", statistic, igamc(3.0, statistic / 2.0))
+}
+
+/// Both p-values of the serial test for overlapping m-bit patterns.
+pub fn serial(bits: &[u8], m: usize) -> Vec<TestResult> {
+    let psi = |m: usize| psi_squared(bits, m);
+
+    let first = psi(m) - psi(m - 1);
+    let second = psi(m) - 2.0 * psi(m - 1) + psi(m - 2);
+
+    vec![
+        TestResult::new(
+            "Serial 1",
+            first,
+            igamc(2_f64.powi(m as i32 - 2), first / 2.0),
+        ),
+        TestResult::new(
+            "Serial 2",
+            second,
+            igamc(2_f64.powi(m as i32 - 3), second / 2.0),
+        ),
+    ]
+}
+
+pub fn approximate_entropy(bits: &[u8], m: usize) -> TestResult {
+    let n = bits.len() as f64;
+
+    let phi = |m: usize| {
+        if m == 0 {
+            return 0.0;
+        }
+
+        pattern_counts(bits, m)
+            .iter()
+            .filter(|count| **count > 0)
+            .map(|count| {
+                let pi = *count as f64 / n;
+                pi * pi.ln()
+            })
+            .sum::<f64>()
+    };
+
+    let entropy = phi(m) - phi(m + 1);
+    let statistic = 2.0 * n * (2_f64.ln() - entropy);
+
+    TestResult::new(
+        "Approximate entropy",
+        statistic,
+        igamc(2_f64.powi(m as i32 - 1), statistic / 2.0),
+    )
+}
+
+/// Forward and backward cumulative sums.
+pub fn cumulative_sums(bits: &[u8]) -> Vec<TestResult> {
+    let forward = cumulative_sum(bits.iter());
+    let backward = cumulative_sum(bits.iter().rev());
+
+    vec![
+        TestResult::new("Cumulative sums (forward)", forward.0, forward.1),
+        TestResult::new("Cumulative sums (backward)", backward.0, backward.1),
+    ]
+}
+
+/// Chi-square test of visit counts per cycle for the states -4..=4 of the walk.
+pub fn random_excursions(bits: &[u8]) -> Vec<TestResult> {
+    let cycles = walk_cycles(bits);
+    let j = cycles.len() as f64;
+
+    [-4, -3, -2, -1, 1, 2, 3, 4]
+        .into_iter()
+        .map(|x: i64| {
+            let mut observed = [0_u64; 6];
+
+            for cycle in &cycles {
+                let visits = cycle.iter().filter(|s| **s == x).count();
+                observed[visits.min(5)] += 1;
+            }
+
+            let a = 1.0 - 1.0 / (2.0 * x.abs() as f64);
+            let b = 1.0 / (4.0 * (x * x) as f64);
+
+            let probabilities = [
+                1.0 - 1.0 / (2.0 * x.abs() as f64),
+                b,
+                b * a,
+                b * a * a,
+                b * a * a * a,
+                a.powi(4) / (2.0 * x.abs() as f64),
+            ];
+
+            let statistic = chi_statistic(&observed, &probabilities, j);
+
+            TestResult::new(
+                &format!("Random excursions (x = {})", x),
+                statistic,
+                igamc(2.5, statistic / 2.0),
+            )
+        })
+        .collect()
+}
+
+/// Total visits to the states -9..=9 of the walk compared to the number of cycles.
+pub fn random_excursions_variant(bits: &[u8]) -> Vec<TestResult> {
+    let cycles = walk_cycles(bits);
+    let j = cycles.len() as f64;
+
+    (-9..=9_i64)
+        .filter(|x| *x != 0)
+        .map(|x| {
+            let visits = cycles.iter().flatten().filter(|s| **s == x).count() as f64;
+            let p_value =
+                erfc((visits - j).abs() / (2.0 * j * (4.0 * x.abs() as f64 - 2.0)).sqrt());
+
+            TestResult::new(
+                &format!("Random excursions variant (x = {})", x),
+                visits,
+                p_value,
+            )
+        })
+        .collect()
+}
+
+fn ones(bits: &[u8]) -> usize {
+    bits.iter().filter(|bit| **bit == 1).count()
+}
+
+fn chi_statistic(observed: &[u64], probabilities: &[f64], n: f64) -> f64 {
+    observed
+        .iter()
+        .zip(probabilities)
+        .map(|(o, p)| (*o as f64 - n * p).powi(2) / (n * p))
+        .sum()
+}
+
+fn gf2_rank(rows: &mut [u32]) -> usize {
+    let mut rank = 0;
+
+    for column in (0..32).rev() {
+        let mask = 1 << column;
+
+        if let Some(pivot) = (rank..rows.len()).find(|i| rows[*i] & mask != 0) {
+            rows.swap(rank, pivot);
+
+            for i in 0..rows.len() {
+                if i != rank && rows[i] & mask != 0 {
+                    rows[i] ^= rows[rank];
+                }
+            }
+
+            rank += 1;
+        }
+    }
+
+    rank
+}
+
+/// Length of the shortest LFSR that generates the block.
+pub fn berlekamp_massey(bits: &[u8]) -> usize {
+    let n = bits.len();
+    let mut c = vec![0_u8; n + 1];
+    let mut b = vec![0_u8; n + 1];
+    c[0] = 1;
+    b[0] = 1;
+
+    let mut length = 0;
+    let mut m = -1_i64;
+
+    for i in 0..n {
+        let discrepancy = (1..=length).fold(bits[i], |d, j| d ^ (c[j] & bits[i - j]));
+
+        if discrepancy == 1 {
+            let previous = c.clone();
+            let shift = (i as i64 - m) as usize;
+
+            for j in shift..=n {
+                c[j] ^= b[j - shift];
+            }
+
+            if 2 * length <= i {
+                length = i + 1 - length;
+                m = i as i64;
+                b = previous;
+            }
+        }
+    }
+
+    length
+}
+
+// Counts of every overlapping m-bit pattern, the sequence wrapped around its end
+fn pattern_counts(bits: &[u8], m: usize) -> Vec<u64> {
+    let n = bits.len();
+    let mask = (1_usize << m) - 1;
+    let mut counts = vec![0; 1 << m];
+
+    let mut pattern = bits[..m - 1]
+        .iter()
+        .fold(0, |acc, bit| (acc << 1) | *bit as usize);
+
+    for i in 0..n {
+        pattern = ((pattern << 1) | bits[(i + m - 1) % n] as usize) & mask;
+        counts[pattern] += 1;
+    }
+
+    counts
+}
+
+fn psi_squared(bits: &[u8], m: usize) -> f64 {
+    if m == 0 {
+        return 0.0;
+    }
+
+    let n = bits.len() as f64;
+    let sum = pattern_counts(bits, m)
+        .iter()
+        .map(|count| (*count as f64).powi(2))
+        .sum::<f64>();
+
+    2_f64.powi(m as i32) / n * sum - n
+}
+
+fn cumulative_sum<'a, I: Iterator<Item = &'a u8>>(bits: I) -> (f64, f64) {
+    let mut n = 0.0_f64;
+    let mut sum = 0_i64;
+    let mut z = 0_i64;
+
+    for bit in bits {
+        sum += 2 * *bit as i64 - 1;
+        z = z.max(sum.abs());
+        n += 1.0;
+    }
+
+    let z = z as f64;
+    let root = n.sqrt();
+
+    // bounds truncated towards zero like the reference implementation
+    let first = ((-n / z + 1.0) / 4.0) as i64..=((n / z - 1.0) / 4.0) as i64;
+    let second = ((-n / z - 3.0) / 4.0) as i64..=((n / z - 1.0) / 4.0) as i64;
+
+    let p_value = 1.0
+        - first
+            .map(|k| {
+                normal_cdf((4 * k + 1) as f64 * z / root)
+                    - normal_cdf((4 * k - 1) as f64 * z / root)
+            })
+            .sum::<f64>()
+        + second
+            .map(|k| {
+                normal_cdf((4 * k + 3) as f64 * z / root)
+                    - normal_cdf((4 * k + 1) as f64 * z / root)
+            })
+            .sum::<f64>();
+
+    (z, p_value)
+}
+
+// Partial sums of the +-1 walk split into cycles between returns to zero, the walk
+// is closed with a final zero
+fn walk_cycles(bits: &[u8]) -> Vec<Vec<i64>> {
+    let mut cycles = Vec::new();
+    let mut cycle = Vec::new();
+    let mut sum = 0_i64;
+
+    for bit in bits {
+        sum += 2 * *bit as i64 - 1;
+
+        if sum == 0 {
+            cycles.push(std::mem::take(&mut cycle));
+        } else {
+            cycle.push(sum);
+        }
+    }
+
+    if !cycle.is_empty() || cycles.is_empty() {
+        cycles.push(cycle);
+    }
+
+    cycles
+}
+
+fn zero_crossings(bits: &[u8]) -> usize {
+    walk_cycles(bits).len()
+}
+
+// DFT of any length as a power-of-two convolution
+fn bluestein(signal: &[Complex64]) -> Vec<Complex64> {
+    let n = signal.len();
+    let size = (2 * n).next_power_of_two();
+
+    // e^(-pi i k^2 / n), k^2 reduced mod 2n to keep the angle small
+    let chirp = (0..n)
+        .map(|k| Complex64::from_polar(1.0, -PI * ((k * k) % (2 * n)) as f64 / n as f64))
+        .collect::<Vec<_>>();
+
+    let mut a = vec![Complex64::new(0.0, 0.0); size];
+    let mut b = vec![Complex64::new(0.0, 0.0); size];
+
+    for k in 0..n {
+        a[k] = signal[k] * chirp[k];
+        b[k] = chirp[k].conj();
+
+        if k > 0 {
+            b[size - k] = chirp[k].conj();
+        }
+    }
+
+    fft(&mut a, false);
+    fft(&mut b, false);
+
+    for (x, y) in a.iter_mut().zip(&b) {
+        *x *= y;
+    }
+
+    fft(&mut a, true);
+
+    (0..n).map(|k| a[k] * chirp[k] / size as f64).collect()
+}
+
+// Iterative radix-2 FFT, the inverse is left unscaled
+fn fft(data: &mut [Complex64], inverse: bool) {
+    let n = data.len();
+
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut length = 2;
+
+    while length <= n {
+        let root = Complex64::from_polar(1.0, sign * 2.0 * PI / length as f64);
+
+        for start in (0..n).step_by(length) {
+            let mut w = Complex64::new(1.0, 0.0);
+
+            for k in 0..length / 2 {
+                let even = data[start + k];
+                let odd = data[start + k + length / 2] * w;
+
+                data[start + k] = even + odd;
+                data[start + k + length / 2] = even - odd;
+
+                w *= root;
+            }
+        }
+
+        length <<= 1;
+    }
+}
@@ -0,0 +1,85 @@
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// How generated numbers are written out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    // space separated decimals on a single line
+    Decimal,
+    // one decimal per line
+    Lines,
+    // one zero padded hexadecimal per line
+    Hex,
+    // `index,value` rows under a header
+    Csv,
+    // raw little endian words, 4 bytes each when the modulus fits into 32 bits, else 8
+    Binary,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "decimal" => Ok(Format::Decimal),
+            "lines" => Ok(Format::Lines),
+            "hex" => Ok(Format::Hex),
+            "csv" => Ok(Format::Csv),
+            "binary" => Ok(Format::Binary),
+            _ => Err(format!(
+                "unknown output format '{}', expected decimal, lines, hex, csv or binary",
+                s
+            )),
+        }
+    }
+}
+
+/// Streams `numbers` into `out` one by one, nothing is collected. Values lie in
+/// `0..modulus`, or anywhere in `u64` for `None`, which sets the binary and hex widths.
+pub fn write_numbers<W: Write, I: Iterator<Item = u64>>(
+    out: &mut W,
+    format: Format,
+    modulus: Option<u64>,
+    numbers: I,
+) -> io::Result<()> {
+    let narrow = modulus.is_some_and(|m| m <= 1 << 32);
+
+    match format {
+        Format::Decimal => {
+            for number in numbers {
+                write!(out, "{} ", number)?;
+            }
+            writeln!(out)?;
+        }
+        Format::Lines => {
+            for number in numbers {
+                writeln!(out, "{}", number)?;
+            }
+        }
+        Format::Hex => {
+            let width = if narrow { 8 } else { 16 };
+
+            for number in numbers {
+                writeln!(out, "{:0width$x}", number, width = width)?;
+            }
+        }
+        Format::Csv => {
+            writeln!(out, "index,value")?;
+
+            for (index, number) in numbers.enumerate() {
+                writeln!(out, "{},{}", index, number)?;
+            }
+        }
+        Format::Binary => {
+            for number in numbers {
+                if narrow {
+                    out.write_all(&(number as u32).to_le_bytes())?;
+                } else {
+                    out.write_all(&number.to_le_bytes())?;
+                }
+            }
+        }
+    }
+
+    out.flush()
+}
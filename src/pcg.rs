@@ -0,0 +1,81 @@
+use crate::generator::{fill_bytes, Generator};
+use rand::RngCore;
+
+const MULTIPLIER: u64 = 6364136223846793005;
+
+/// O'Neill's PCG32 (XSH RR 64/32): a 64-bit LCG whose state is permuted into 32 output
+/// bits, so the weak low bits of the LCG never show up directly.
+#[derive(Debug, Clone)]
+pub struct PCG32 {
+    state: u64,
+    increment: u64,
+}
+
+impl PCG32 {
+    /// `pcg32_srandom_r` of the reference implementation: `stream` selects one of 2^63
+    /// sequences, `seed` the position in it.
+    pub fn new(seed: u64, stream: u64) -> Self {
+        let mut pcg = Self {
+            state: 0,
+            increment: (stream << 1) | 1,
+        };
+
+        pcg.step();
+        pcg.state = pcg.state.wrapping_add(seed);
+        pcg.step();
+
+        pcg
+    }
+
+    fn output(&mut self) -> u32 {
+        let old = self.state;
+        self.step();
+
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        let rotation = (old >> 59) as u32;
+
+        xorshifted.rotate_right(rotation)
+    }
+
+    fn step(&mut self) {
+        self.state = self
+            .state
+            .wrapping_mul(MULTIPLIER)
+            .wrapping_add(self.increment);
+    }
+}
+
+impl RngCore for PCG32 {
+    fn next_u32(&mut self) -> u32 {
+        self.output()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let low = self.output() as u64;
+
+        ((self.output() as u64) << 32) | low
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        fill_bytes(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl Generator for PCG32 {
+    fn modulus(&self) -> Option<u64> {
+        Some(1 << 32)
+    }
+}
+
+impl Iterator for PCG32 {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.output() as u64)
+    }
+}
@@ -0,0 +1,187 @@
+use std::fmt::Write;
+
+// blank border around the unit square, as a fraction of the picture
+const MARGIN: f64 = 0.05;
+
+type Point = (f64, f64);
+
+/// Points and line segments in the unit square, y pointing up, rendered to SVG or PPM.
+#[derive(Debug, Clone)]
+pub struct Canvas {
+    size: u32,
+    points: Vec<Point>,
+    lines: Vec<(Point, Point)>,
+}
+
+impl Canvas {
+    pub fn new(size: u32) -> Self {
+        Self {
+            size,
+            points: Vec::new(),
+            lines: Vec::new(),
+        }
+    }
+
+    pub fn point(&mut self, point: Point) {
+        self.points.push(point);
+    }
+
+    pub fn line(&mut self, from: Point, to: Point) {
+        self.lines.push((from, to));
+    }
+
+    pub fn to_svg(&self) -> String {
+        let mut svg = String::new();
+
+        // writing into a String never fails
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{0}" viewBox="0 0 {0} {0}">"#,
+            self.size
+        );
+        let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#);
+
+        for (from, to) in &self.lines {
+            let (x1, y1) = self.pixel(*from);
+            let (x2, y2) = self.pixel(*to);
+            let _ = writeln!(
+                svg,
+                r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="gray"/>"#,
+                x1, y1, x2, y2
+            );
+        }
+
+        for point in &self.points {
+            let (x, y) = self.pixel(*point);
+            let _ = writeln!(svg, r#"<circle cx="{:.1}" cy="{:.1}" r="1"/>"#, x, y);
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Binary PPM (P6), black points and gray lines on white.
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let size = self.size as usize;
+        let mut pixels = vec![255_u8; size * size * 3];
+
+        let mut plot = |(x, y): Point, shade: u8| {
+            let (column, row) = (x.round(), y.round());
+
+            if (0.0..size as f64).contains(&column) && (0.0..size as f64).contains(&row) {
+                let offset = (row as usize * size + column as usize) * 3;
+                pixels[offset..offset + 3].fill(shade);
+            }
+        };
+
+        for (from, to) in &self.lines {
+            let (x1, y1) = self.pixel(*from);
+            let (x2, y2) = self.pixel(*to);
+            let steps = (x2 - x1).abs().max((y2 - y1).abs()).ceil().max(1.0);
+
+            for step in 0..=steps as usize {
+                let t = step as f64 / steps;
+                plot((x1 + t * (x2 - x1), y1 + t * (y2 - y1)), 160);
+            }
+        }
+
+        for point in &self.points {
+            plot(self.pixel(*point), 0);
+        }
+
+        let mut ppm = format!("P6\n{0} {0}\n255\n", self.size).into_bytes();
+        ppm.extend(pixels);
+        ppm
+    }
+
+    fn pixel(&self, (x, y): Point) -> Point {
+        let size = self.size as f64;
+        let scale = size * (1.0 - 2.0 * MARGIN);
+
+        (size * MARGIN + x * scale, size * MARGIN + (1.0 - y) * scale)
+    }
+}
+
+/// Overlapping pairs (u_i, u_(i+1)) of values in [0, 1): the lines of an LCG show up here.
+pub fn pairs(values: &[f64], size: u32) -> Canvas {
+    let mut canvas = Canvas::new(size);
+
+    let corners = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+    for (index, corner) in corners.iter().enumerate() {
+        canvas.line(*corner, corners[(index + 1) % 4]);
+    }
+
+    for pair in values.windows(2) {
+        canvas.point((pair[0], pair[1]));
+    }
+
+    canvas
+}
+
+/// Overlapping triples in the unit cube, turned by `yaw` around the vertical axis and
+/// tilted by `pitch` towards the viewer (both in degrees), then projected orthographically.
+/// Planes show up as lines when they are seen edge on.
+pub fn triples(values: &[f64], size: u32, yaw: f64, pitch: f64) -> Canvas {
+    let (yaw, pitch) = (yaw.to_radians(), pitch.to_radians());
+
+    // the cube is centered at the origin, its diagonal is at most sqrt(3)
+    let project = |x: f64, y: f64, z: f64| {
+        let (x, y, z) = (x - 0.5, y - 0.5, z - 0.5);
+
+        let (x, y) = (x * yaw.cos() - y * yaw.sin(), x * yaw.sin() + y * yaw.cos());
+        let z = z * pitch.cos() - y * pitch.sin();
+
+        (0.5 + x / 3_f64.sqrt(), 0.5 + z / 3_f64.sqrt())
+    };
+
+    let mut canvas = Canvas::new(size);
+
+    // the twelve edges join corners that differ in a single coordinate
+    for corner in 0..8_u32 {
+        for axis in 0..3 {
+            if corner & (1 << axis) == 0 {
+                let from = corner;
+                let to = corner | (1 << axis);
+                let coordinates = |c: u32| ((c & 1) as f64, (c >> 1 & 1) as f64, (c >> 2) as f64);
+
+                let (x1, y1, z1) = coordinates(from);
+                let (x2, y2, z2) = coordinates(to);
+                canvas.line(project(x1, y1, z1), project(x2, y2, z2));
+            }
+        }
+    }
+
+    for triple in values.windows(3) {
+        canvas.point(project(triple[0], triple[1], triple[2]));
+    }
+
+    canvas
+}
+
+/// Histogram of values in [0, 1) with `bins` equal bins, bars at most `width` characters.
+pub fn histogram(values: &[f64], bins: usize, width: usize) -> String {
+    let mut counts = vec![0_usize; bins];
+
+    for value in values {
+        counts[((value * bins as f64) as usize).min(bins - 1)] += 1;
+    }
+
+    let highest = counts.iter().copied().max().unwrap_or(0).max(1);
+    let mut output = String::new();
+
+    for (index, count) in counts.iter().enumerate() {
+        let bar = "#".repeat(count * width / highest);
+
+        let _ = writeln!(
+            output,
+            "[{:.3}, {:.3}) {:<width$} {}",
+            index as f64 / bins as f64,
+            (index + 1) as f64 / bins as f64,
+            bar,
+            count,
+            width = width
+        );
+    }
+
+    output
+}
@@ -0,0 +1,309 @@
+use crate::generator::{fill_bytes, Generator};
+use crate::lcg::{Lcg, Word};
+use num::NumCast;
+use rand::RngCore;
+
+/// Parameters of a well known LCG together with the way its library turns the state
+/// into output, so that the outputs match the reference implementation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Preset {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub modulus: u128,
+    pub multiplier: u128,
+    pub increment: u128,
+    // xor-ed into the seed before it becomes the state, java.util.Random scrambles it
+    pub scramble: u128,
+    // outputs are `bits` bits of the state from bit `shift` up, the whole state for None
+    pub shift: u32,
+    pub bits: Option<u32>,
+}
+
+pub const PRESETS: [Preset; 7] = [
+    Preset {
+        name: "minstd",
+        description: "Park-Miller minimal standard, C++ minstd_rand0",
+        modulus: (1 << 31) - 1,
+        multiplier: 16807,
+        increment: 0,
+        scramble: 0,
+        shift: 0,
+        bits: None,
+    },
+    Preset {
+        name: "minstd_rand",
+        description: "Park-Miller revised multiplier, C++ minstd_rand",
+        modulus: (1 << 31) - 1,
+        multiplier: 48271,
+        increment: 0,
+        scramble: 0,
+        shift: 0,
+        bits: None,
+    },
+    Preset {
+        name: "glibc_type0",
+        description: "glibc random() after initstate() with the 8 byte TYPE_0 state",
+        modulus: 1 << 31,
+        multiplier: 1103515245,
+        increment: 12345,
+        scramble: 0,
+        shift: 0,
+        bits: None,
+    },
+    Preset {
+        name: "msvc",
+        description: "Microsoft Visual C rand(), bits 16..30 of the state",
+        modulus: 1 << 32,
+        multiplier: 214013,
+        increment: 2531011,
+        scramble: 0,
+        shift: 16,
+        bits: Some(15),
+    },
+    Preset {
+        name: "nr",
+        description: "Numerical Recipes ranqd1",
+        modulus: 1 << 32,
+        multiplier: 1664525,
+        increment: 1013904223,
+        scramble: 0,
+        shift: 0,
+        bits: None,
+    },
+    Preset {
+        name: "mmix",
+        description: "Knuth's MMIX",
+        modulus: 1 << 64,
+        multiplier: 6364136223846793005,
+        increment: 1442695040888963407,
+        scramble: 0,
+        shift: 0,
+        bits: None,
+    },
+    Preset {
+        name: "java",
+        description: "java.util.Random nextInt(), bits 16..47 of the state",
+        modulus: 1 << 48,
+        multiplier: 0x5DEECE66D,
+        increment: 11,
+        scramble: 0x5DEECE66D,
+        shift: 16,
+        bits: Some(32),
+    },
+];
+
+impl Preset {
+    pub fn find(name: &str) -> Option<&'static Preset> {
+        PRESETS.iter().find(|preset| preset.name == name)
+    }
+
+    /// Bits of the narrowest word, 32, 64 or 128, that holds the modulus.
+    pub fn width(&self) -> u32 {
+        if self.modulus <= u32::MAX as u128 {
+            32
+        } else if self.modulus <= u64::MAX as u128 {
+            64
+        } else {
+            128
+        }
+    }
+
+    /// The bare LCG, its iterator yields states rather than the library's outputs.
+    pub fn lcg<W: Word>(&self, seed: u64) -> Lcg<W> {
+        let word = |value: u128| -> W {
+            NumCast::from(value).unwrap_or_else(|| {
+                panic!("{} does not fit into u{} for {}", value, W::BITS, self.name)
+            })
+        };
+
+        let mut state = (seed as u128 ^ self.scramble) % self.modulus;
+
+        // a multiplicative generator would stay at 0, C++ seeds it with 1 instead
+        if self.increment == 0 && state == 0 {
+            state = 1;
+        }
+
+        Lcg::new(
+            word(self.modulus),
+            word(self.multiplier),
+            word(self.increment),
+            word(state),
+        )
+    }
+
+    /// Yields exactly what the library function returns, as unsigned numbers.
+    pub fn generator<W: Word>(&self, seed: u64) -> Output<W> {
+        Output {
+            lcg: self.lcg(seed),
+            shift: self.shift,
+            bits: self.bits,
+        }
+    }
+}
+
+/// An LCG that yields a bit field of its state, the way library `rand` functions do.
+#[derive(Debug, Clone)]
+pub struct Output<W: Word> {
+    lcg: Lcg<W>,
+    shift: u32,
+    bits: Option<u32>,
+}
+
+impl<W: Word> Output<W> {
+    fn concatenate(&mut self, bits: u32, total: u32) -> u64 {
+        let mut value = 0_u64;
+        let mut filled = 0;
+
+        while filled < total {
+            value = (value << bits) | self.next().unwrap_or_default();
+            filled += bits;
+        }
+
+        value
+    }
+}
+
+impl<W: Word> Generator for Output<W> {
+    fn modulus(&self) -> Option<u64> {
+        match self.bits {
+            Some(bits) => Some(1 << bits),
+            None => self.lcg.modulus().to_u64(),
+        }
+    }
+}
+
+/// The whole state is rejection sampled by the LCG itself, a bit field of a power of two
+/// modulus is uniform already, so its outputs are just concatenated.
+impl<W: Word> RngCore for Output<W> {
+    fn next_u32(&mut self) -> u32 {
+        match self.bits {
+            Some(bits) => self.concatenate(bits, u32::BITS) as u32,
+            None => self.lcg.next_u32(),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match self.bits {
+            Some(bits) => self.concatenate(bits, u64::BITS),
+            None => self.lcg.next_u64(),
+        }
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        fill_bytes(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl<W: Word> Iterator for Output<W> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let state = self.lcg.next()? >> self.shift as usize;
+
+        let field = match self.bits {
+            Some(bits) => state & ((W::one() << bits as usize) - W::one()),
+            None => state,
+        };
+
+        field.to_u64()
+    }
+}
+
+/// glibc `rand()`: the TYPE_3 additive feedback generator `r[i] = r[i - 3] + r[i - 31]`
+/// over 31 words, which `srand` fills with the 16807 LCG before dropping 310 outputs.
+#[derive(Debug, Clone)]
+pub struct GlibcRand {
+    state: [u32; GlibcRand::DEGREE],
+    front: usize,
+    rear: usize,
+}
+
+impl GlibcRand {
+    const DEGREE: usize = 31;
+    const SEPARATION: usize = 3;
+
+    /// `srand(seed)`, 0 is taken as 1 like glibc does.
+    pub fn new(seed: u32) -> Self {
+        let mut state = [0; Self::DEGREE];
+        state[0] = seed.max(1);
+
+        // 16807 * x mod 2^31 - 1 by Schrage's method on a signed 32 bit word, as glibc
+        // does, so seeds above 2^31 start out negative there too
+        let mut word = state[0] as i32;
+
+        for slot in state.iter_mut().skip(1) {
+            word = 16807 * (word % 127773) - 2836 * (word / 127773);
+
+            if word < 0 {
+                word += i32::MAX;
+            }
+
+            *slot = word as u32;
+        }
+
+        let mut rand = Self {
+            state,
+            front: Self::SEPARATION,
+            rear: 0,
+        };
+
+        for _ in 0..10 * Self::DEGREE {
+            rand.output();
+        }
+
+        rand
+    }
+
+    fn output(&mut self) -> u32 {
+        self.state[self.front] = self.state[self.front].wrapping_add(self.state[self.rear]);
+        let result = self.state[self.front] >> 1;
+
+        self.front = (self.front + 1) % Self::DEGREE;
+        self.rear = (self.rear + 1) % Self::DEGREE;
+
+        result
+    }
+}
+
+/// Outputs have 31 bits, the upper 16 of two of them make a `u32`.
+impl RngCore for GlibcRand {
+    fn next_u32(&mut self) -> u32 {
+        let high = self.output() >> 15;
+
+        (high << 16) | (self.output() >> 15)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let low = self.next_u32() as u64;
+
+        ((self.next_u32() as u64) << 32) | low
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        fill_bytes(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl Generator for GlibcRand {
+    fn modulus(&self) -> Option<u64> {
+        Some(1 << 31)
+    }
+}
+
+impl Iterator for GlibcRand {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.output() as u64)
+    }
+}
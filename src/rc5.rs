@@ -1,9 +1,10 @@
 use crate::lcg::LCG;
-use crate::md5::MD5;
+use crate::md5::{Digest, MD5};
 use num::traits::{AsPrimitive, WrappingAdd, WrappingSub};
 use num::{NumCast, PrimInt};
 use std::cmp::max;
 use std::fmt::Debug;
+use std::marker::PhantomData;
 use std::mem::size_of;
 use std::ops;
 
@@ -36,9 +37,9 @@ pub trait Word: // u16 u32 u64
 }
 
 pub enum Flags {
-    ECB,
-    CBC,
-    CBC_MD5,
+    Ecb,
+    Cbc,
+    CbcMd5,
 }
 
 macro_rules! impl_word {
@@ -83,7 +84,7 @@ where
 }
 
 pub struct RC5<W: Word> {
-    word_size: W,
+    word_size: PhantomData<W>,
     rounds: usize,
     octets: usize,
     extended_part: usize,
@@ -97,7 +98,7 @@ where
 {
     pub fn new(rounds: usize, octets: usize, flag: Flags) -> Self {
         Self {
-            word_size: W::ZERO,
+            word_size: PhantomData,
             rounds,
             octets,
             extended_part: usize::default(),
@@ -122,66 +123,41 @@ where
 
     fn key_expand(&self, key: &[u8]) -> Vec<W> {
         let mut words = match self.flag {
-            Flags::ECB => self.key_to_words(&key),
-            Flags::CBC => self.key_to_words(&key),
-            Flags::CBC_MD5 => {
-                let hashed_key = MD5::from(String::from_utf8_lossy(key).to_string().as_str());
+            Flags::Ecb => self.key_to_words(key),
+            Flags::Cbc => self.key_to_words(key),
+            Flags::CbcMd5 => {
+                let hashed_key = MD5::digest(key);
+
+                // the digest as four big endian words, added up
+                let sum = |digest: Digest| -> u128 {
+                    digest
+                        .as_bytes()
+                        .chunks(4)
+                        .map(|word| {
+                            u32::from_be_bytes([word[0], word[1], word[2], word[3]]) as u128
+                        })
+                        .sum()
+                };
 
                 let mut bytes = Vec::<u8>::new();
 
                 match self.octets {
                     8 => {
-                        let temp = hashed_key
-                            .chars()
-                            .collect::<Vec<_>>()
-                            .chunks(8)
-                            .map(|x| x.iter().collect::<String>())
-                            .collect::<Vec<_>>();
-
-                        let low_bytes: u128 = temp[2..]
-                            .iter()
-                            .map(|x| u128::from_str_radix(x, 16).expect("Unable to parse as u128"))
-                            .sum();
-
-                        bytes.extend(low_bytes.to_be_bytes());
+                        // only the last two words
+                        let mut low = hashed_key;
+                        low.0[..8].fill(0);
+
+                        bytes.extend(sum(low).to_be_bytes());
                     }
                     16 => {
-                        let temp: u128 = hashed_key
-                            .chars()
-                            .collect::<Vec<_>>()
-                            .chunks(8)
-                            .map(|x| x.iter().collect::<String>())
-                            .collect::<Vec<_>>()
-                            .iter()
-                            .map(|x| u128::from_str_radix(x, 16).expect("Unable to parse as u128"))
-                            .sum();
-
-                        bytes.extend(temp.to_be_bytes());
+                        bytes.extend(sum(hashed_key).to_be_bytes());
                     }
                     32 => {
-                        let high_bytes: u128 = hashed_key
-                            .chars()
-                            .collect::<Vec<_>>()
-                            .chunks(8)
-                            .map(|x| x.iter().collect::<String>())
-                            .collect::<Vec<_>>()
-                            .iter()
-                            .map(|x| u128::from_str_radix(x, 16).expect("Unable to parse as u128"))
-                            .sum();
-
-                        bytes.extend(high_bytes.to_be_bytes());
-
-                        let low_bytes: u128 = MD5::from(hashed_key.as_str())
-                            .chars()
-                            .collect::<Vec<_>>()
-                            .chunks(8)
-                            .map(|x| x.iter().collect::<String>())
-                            .collect::<Vec<_>>()
-                            .iter()
-                            .map(|x| u128::from_str_radix(x, 16).expect("Unable to parse as u128"))
-                            .sum();
-
-                        bytes.extend(low_bytes.to_be_bytes());
+                        bytes.extend(sum(hashed_key).to_be_bytes());
+
+                        // the second half comes from hashing the printed first digest
+                        let rehashed = MD5::digest(hashed_key.to_upper_hex().as_bytes());
+                        bytes.extend(sum(rehashed).to_be_bytes());
                     }
                     _ => {
                         panic!("unknown word size!")
@@ -285,8 +261,7 @@ where
             ciphertext.extend(
                 self.encrypt_block(block, key)
                     .into_iter()
-                    .map(|w| w.to_le_bytes())
-                    .flatten(),
+                    .flat_map(|w| w.to_le_bytes()),
             )
         }
 
@@ -308,8 +283,7 @@ where
             plain.extend(
                 self.decrypt_block(block, key)
                     .into_iter()
-                    .map(|w| w.to_le_bytes())
-                    .flatten(),
+                    .flat_map(|w| w.to_le_bytes()),
             )
         }
 
@@ -317,12 +291,12 @@ where
     }
 
     pub fn encrypt_cbc(&mut self, plain: &[u8], key: &[u8]) -> Vec<u8> {
-        let mut plain_clone = plain.clone().to_vec();
+        let mut plain_clone = plain.to_vec();
         let plaintext_len = plain.len();
         let word_bytes = size_of::<W>();
         let block_size = 2 * word_bytes;
 
-        let steps = (plaintext_len + (block_size - 1)) / block_size;
+        let steps = plaintext_len.div_ceil(block_size);
 
         self.extended_part = steps * block_size - plaintext_len;
 
@@ -350,7 +324,7 @@ where
                 _ => [block[0] ^ ct[0], block[1] ^ ct[1]],
             };
 
-            ct = self.encrypt_block(pt, &key);
+            ct = self.encrypt_block(pt, key);
 
             ciphertext.extend(ct[0].to_le_bytes());
             ciphertext.extend(ct[1].to_le_bytes());
@@ -382,7 +356,7 @@ where
 
             let ct = [block[0], block[1]];
 
-            let pt = self.decrypt_block(ct, &key);
+            let pt = self.decrypt_block(ct, key);
 
             let pt = match round {
                 0 => [pt[0] ^ iv[0], pt[1] ^ iv[1]],
@@ -0,0 +1,465 @@
+use crate::hash;
+use digest::consts::{U20, U64};
+use digest::core_api::BlockSizeUser;
+use digest::{FixedOutput, FixedOutputReset, HashMarker, Output, OutputSizeUser, Reset, Update};
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+/// 160 bit SHA-1 value.
+pub type Digest = hash::Digest<20>;
+
+const IV: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+// one constant per 20 steps
+const K: [u32; 4] = [0x5A827999, 0x6ED9EBA1, 0x8F1BBCDC, 0xCA62C1D6];
+
+// Every recompression starts from the state before one of these steps.
+const SHARED_STEPS: [usize; 2] = [58, 65];
+
+/// Disturbance vectors checked for, the 32 of the `sha1_dvs` table of the SHA-1 collision
+/// detection library of Stevens and Shumow, which covers SHAttered and every other
+/// published attack.
+pub static DISTURBANCES: [Disturbance; 32] = [
+    Disturbance::new(Kind::I, 43, 0),
+    Disturbance::new(Kind::I, 44, 0),
+    Disturbance::new(Kind::I, 45, 0),
+    Disturbance::new(Kind::I, 46, 0),
+    Disturbance::new(Kind::I, 46, 2),
+    Disturbance::new(Kind::I, 47, 0),
+    Disturbance::new(Kind::I, 47, 2),
+    Disturbance::new(Kind::I, 48, 0),
+    Disturbance::new(Kind::I, 48, 2),
+    Disturbance::new(Kind::I, 49, 0),
+    Disturbance::new(Kind::I, 49, 2),
+    Disturbance::new(Kind::I, 50, 0),
+    Disturbance::new(Kind::I, 50, 2),
+    Disturbance::new(Kind::I, 51, 0),
+    Disturbance::new(Kind::I, 51, 2),
+    Disturbance::new(Kind::I, 52, 0),
+    Disturbance::new(Kind::II, 45, 0),
+    Disturbance::new(Kind::II, 46, 0),
+    Disturbance::new(Kind::II, 46, 2),
+    Disturbance::new(Kind::II, 47, 0),
+    Disturbance::new(Kind::II, 48, 0),
+    Disturbance::new(Kind::II, 49, 0),
+    Disturbance::new(Kind::II, 49, 2),
+    Disturbance::new(Kind::II, 50, 0),
+    Disturbance::new(Kind::II, 50, 2),
+    Disturbance::new(Kind::II, 51, 0),
+    Disturbance::new(Kind::II, 51, 2),
+    Disturbance::new(Kind::II, 52, 0),
+    Disturbance::new(Kind::II, 53, 0),
+    Disturbance::new(Kind::II, 54, 0),
+    Disturbance::new(Kind::II, 55, 0),
+    Disturbance::new(Kind::II, 56, 0),
+];
+
+// Boolean function of step t
+fn f(t: usize, b: u32, c: u32, d: u32) -> u32 {
+    match t / 20 {
+        0 => (b & c) | (!b & d),
+        2 => (b & c) | (b & d) | (c & d),
+        _ => b ^ c ^ d,
+    }
+}
+
+/// Step `t` on the state a, b, c, d, e.
+pub fn step(state: &mut [u32; 5], t: usize, w: u32) {
+    let [a, b, c, d, e] = *state;
+
+    let temp = a
+        .rotate_left(5)
+        .wrapping_add(f(t, b, c, d))
+        .wrapping_add(e)
+        .wrapping_add(K[t / 20])
+        .wrapping_add(w);
+
+    *state = [temp, a, b.rotate_left(30), c, d];
+}
+
+/// Undoes step `t`, the state before it from the state after it.
+pub fn unstep(state: &mut [u32; 5], t: usize, w: u32) {
+    let [temp, a, b, c, d] = *state;
+    let b = b.rotate_right(30);
+
+    let e = temp
+        .wrapping_sub(a.rotate_left(5))
+        .wrapping_sub(f(t, b, c, d))
+        .wrapping_sub(K[t / 20])
+        .wrapping_sub(w);
+
+    *state = [a, b, c, d, e];
+}
+
+/// The 80 words of a 64 byte block.
+pub fn expand(block: &[u8]) -> [u32; 80] {
+    let mut w = [0; 80];
+
+    for (word, bytes) in w.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+
+    for t in 16..80 {
+        w[t] = (w[t - 3] ^ w[t - 8] ^ w[t - 14] ^ w[t - 16]).rotate_left(1);
+    }
+
+    w
+}
+
+/// The two families of disturbance vectors of Manuel's classification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    I,
+    II,
+}
+
+/// A disturbance vector I(K, b) or II(K, b): where the local collisions of an attack sit.
+/// Both kinds have words K to K + 15 zero but for 2^b at K + 15, type II also has
+/// 2^(b + 31) at K + 1 and K + 3. The message expansion fills in the rest both ways.
+#[derive(Debug, Clone, Copy)]
+pub struct Disturbance {
+    pub kind: Kind,
+    pub k: usize,
+    pub b: u32,
+    // both messages of the colliding block have the same state before this step
+    pub step: usize,
+    // XOR difference of the expanded messages of the colliding block
+    pub mask: [u32; 80],
+}
+
+impl Disturbance {
+    pub const fn new(kind: Kind, k: usize, b: u32) -> Self {
+        // vector[i] is word i - 5, the corrections reach five steps back
+        let mut vector = [0_u32; 85];
+        let bit = 1_u32 << b;
+
+        vector[k + 20] = bit;
+        if let Kind::II = kind {
+            vector[k + 6] = bit.rotate_left(31);
+            vector[k + 8] = bit.rotate_left(31);
+        }
+
+        let mut i = k + 21;
+        while i < 85 {
+            vector[i] =
+                (vector[i - 3] ^ vector[i - 8] ^ vector[i - 14] ^ vector[i - 16]).rotate_left(1);
+            i += 1;
+        }
+
+        let mut i = k + 5;
+        while i > 0 {
+            i -= 1;
+            vector[i] =
+                vector[i + 16].rotate_right(1) ^ vector[i + 13] ^ vector[i + 8] ^ vector[i + 2];
+        }
+
+        // a disturbance in bit b of step t is corrected in bit b + 5 of step t + 1, bit b
+        // of t + 2 and bit b + 30 of t + 3 to t + 5
+        let mut mask = [0; 80];
+        let mut t = 0;
+        while t < 80 {
+            let i = t + 5;
+            mask[t] = vector[i]
+                ^ vector[i - 1].rotate_left(5)
+                ^ vector[i - 2]
+                ^ vector[i - 3].rotate_left(30)
+                ^ vector[i - 4].rotate_left(30)
+                ^ vector[i - 5].rotate_left(30);
+            t += 1;
+        }
+
+        // the states agree where no local collision is under way, five quiet steps, and
+        // the later such step is taken as sha1dc does
+        let mut s = SHARED_STEPS.len();
+        let step = loop {
+            assert!(s > 0, "no shared state to recompress from");
+            s -= 1;
+
+            let step = SHARED_STEPS[s];
+            let mut quiet = true;
+            let mut i = step;
+            while i < step + 5 {
+                quiet = quiet && vector[i] == 0;
+                i += 1;
+            }
+
+            if quiet {
+                break step;
+            }
+        };
+
+        Self {
+            kind,
+            k,
+            b,
+            step,
+            mask,
+        }
+    }
+
+    /// Whether the block with expanded message `w` is the last block of an attack along
+    /// this vector. `shared` is the state before `self.step` and `output` the chaining
+    /// value after the block. The partner block, `w` XOR the mask, is run back from the
+    /// shared state to find its chaining value and forward to its output: when that
+    /// output is ours, the two blocks collide.
+    pub fn collides(&self, w: &[u32; 80], shared: [u32; 5], output: &[u32; 5]) -> bool {
+        let partner = std::array::from_fn::<u32, 80, _>(|t| w[t] ^ self.mask[t]);
+
+        let mut input = shared;
+        for (t, &word) in partner.iter().enumerate().take(self.step).rev() {
+            unstep(&mut input, t, word);
+        }
+
+        let mut state = shared;
+        for (t, &word) in partner.iter().enumerate().skip(self.step) {
+            step(&mut state, t, word);
+        }
+
+        input
+            .iter()
+            .zip(state)
+            .map(|(input, state)| input.wrapping_add(state))
+            .eq(output.iter().copied())
+    }
+}
+
+impl fmt::Display for Disturbance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}({},{})", self.kind, self.k, self.b)
+    }
+}
+
+/// A block that completes a near-collision attack.
+#[derive(Debug, Clone, Copy)]
+pub struct Collision {
+    // index of the 64 byte block
+    pub block: u64,
+    pub disturbance: &'static Disturbance,
+}
+
+impl fmt::Display for Collision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "SHA-1 collision attack detected in block {} (disturbance vector {})",
+            self.block, self.disturbance
+        )
+    }
+}
+
+impl Error for Collision {}
+
+/// Incremental SHA-1, same API as `Md5`. With collision detection every block is also
+/// checked for being the second half of a collision attack, the counter-cryptanalysis of
+/// Stevens (CRYPTO 2013) that flags SHAttered, at the cost of a recompression per
+/// disturbance vector.
+#[derive(Debug, Clone)]
+pub struct Sha1 {
+    state: [u32; 5],
+    buffer: [u8; 64],
+    buffered: usize,
+    // total input length in bytes, SHA-1 appends it in bits
+    length: u64,
+    // blocks compressed so far, to tell where an attack is
+    blocks: u64,
+    detect: bool,
+    collision: Option<Collision>,
+}
+
+impl Sha1 {
+    pub fn new() -> Self {
+        Self {
+            state: IV,
+            buffer: [0; 64],
+            buffered: 0,
+            length: 0,
+            blocks: 0,
+            detect: false,
+            collision: None,
+        }
+    }
+
+    pub fn with_collision_detection() -> Self {
+        Self {
+            detect: true,
+            ..Self::new()
+        }
+    }
+
+    pub fn update(&mut self, mut input: &[u8]) {
+        self.length = self.length.wrapping_add(input.len() as u64);
+
+        // top up a partial block first
+        if self.buffered > 0 {
+            let take = input.len().min(64 - self.buffered);
+            self.buffer[self.buffered..self.buffered + take].copy_from_slice(&input[..take]);
+            self.buffered += take;
+            input = &input[take..];
+
+            if self.buffered < 64 {
+                return;
+            }
+
+            let block = self.buffer;
+            self.compress(&block);
+            self.buffered = 0;
+        }
+
+        let mut blocks = input.chunks_exact(64);
+        for block in blocks.by_ref() {
+            self.compress(block);
+        }
+
+        let rest = blocks.remainder();
+        self.buffer[..rest.len()].copy_from_slice(rest);
+        self.buffered = rest.len();
+    }
+
+    pub fn finalize(mut self) -> Digest {
+        self.pad();
+
+        self.output()
+    }
+
+    /// The digest, or the first attack block found when detection is on.
+    pub fn finalize_checked(mut self) -> Result<Digest, Collision> {
+        self.pad();
+
+        match self.collision {
+            Some(collision) => Err(collision),
+            None => Ok(self.output()),
+        }
+    }
+
+    pub fn digest(input: &[u8]) -> Digest {
+        let mut sha1 = Self::new();
+        sha1.update(input);
+
+        sha1.finalize()
+    }
+
+    /// Digest of everything `reader` yields, read in chunks in constant memory.
+    pub fn digest_reader<R: Read>(mut reader: R) -> io::Result<Digest> {
+        let mut sha1 = Self::new();
+        io::copy(&mut reader, &mut sha1)?;
+
+        Ok(sha1.finalize())
+    }
+
+    // the 1 bit, zeros up to 56 modulo 64 and the length in bits big endian
+    fn pad(&mut self) {
+        let zeros = (64 + 56 - (self.buffered + 1) % 64) % 64;
+        let mut padding = vec![0x80_u8];
+        padding.resize(1 + zeros, 0);
+        padding.extend_from_slice(&self.length.wrapping_mul(8).to_be_bytes());
+
+        // the length was counted already
+        let length = self.length;
+        self.update(&padding);
+        self.length = length;
+    }
+
+    fn output(&self) -> Digest {
+        let mut digest = [0; 20];
+        for (chunk, word) in digest.chunks_exact_mut(4).zip(self.state) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+
+        hash::Digest(digest)
+    }
+
+    fn compress(&mut self, block: &[u8]) {
+        let w = expand(block);
+
+        let mut state = self.state;
+        let mut shared = [[0; 5]; SHARED_STEPS.len()];
+
+        for (t, &word) in w.iter().enumerate() {
+            if let Some(i) = SHARED_STEPS.iter().position(|&step| step == t) {
+                shared[i] = state;
+            }
+
+            step(&mut state, t, word);
+        }
+
+        for (word, value) in self.state.iter_mut().zip(state) {
+            *word = word.wrapping_add(value);
+        }
+
+        if self.detect && self.collision.is_none() {
+            let block = self.blocks;
+
+            self.collision = DISTURBANCES
+                .iter()
+                .find(|disturbance| {
+                    let i = SHARED_STEPS
+                        .iter()
+                        .position(|&step| step == disturbance.step);
+                    disturbance.collides(&w, shared[i.unwrap_or_default()], &self.state)
+                })
+                .map(|disturbance| Collision { block, disturbance });
+        }
+
+        self.blocks += 1;
+    }
+}
+
+impl Default for Sha1 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Lets `io::copy` stream a file or stdin into the hash.
+impl Write for Sha1 {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// RustCrypto traits, DSA signs and verifies through them
+
+impl HashMarker for Sha1 {}
+
+impl OutputSizeUser for Sha1 {
+    type OutputSize = U20;
+}
+
+impl BlockSizeUser for Sha1 {
+    type BlockSize = U64;
+}
+
+impl Update for Sha1 {
+    fn update(&mut self, data: &[u8]) {
+        Sha1::update(self, data);
+    }
+}
+
+impl FixedOutput for Sha1 {
+    fn finalize_into(self, out: &mut Output<Self>) {
+        out.copy_from_slice(&Sha1::finalize(self).0);
+    }
+}
+
+/// Keeps collision detection on or off.
+impl Reset for Sha1 {
+    fn reset(&mut self) {
+        *self = Self {
+            detect: self.detect,
+            ..Sha1::new()
+        };
+    }
+}
+
+impl FixedOutputReset for Sha1 {
+    fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+        out.copy_from_slice(&self.clone().finalize().0);
+        Reset::reset(self);
+    }
+}
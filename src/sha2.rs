@@ -0,0 +1,421 @@
+pub use crate::hash::Digest;
+use digest::consts::{U128, U28, U32, U48, U64};
+use digest::core_api::BlockSizeUser;
+use digest::{FixedOutput, FixedOutputReset, HashMarker, Output, OutputSizeUser, Reset, Update};
+use std::io::{self, Read, Write};
+
+/// First 32 bits of the fractional parts of the cube roots of the first 64 primes.
+const K256: [u32; 64] = [
+    0x428A2F98, 0x71374491, 0xB5C0FBCF, 0xE9B5DBA5, 0x3956C25B, 0x59F111F1, 0x923F82A4, 0xAB1C5ED5,
+    0xD807AA98, 0x12835B01, 0x243185BE, 0x550C7DC3, 0x72BE5D74, 0x80DEB1FE, 0x9BDC06A7, 0xC19BF174,
+    0xE49B69C1, 0xEFBE4786, 0x0FC19DC6, 0x240CA1CC, 0x2DE92C6F, 0x4A7484AA, 0x5CB0A9DC, 0x76F988DA,
+    0x983E5152, 0xA831C66D, 0xB00327C8, 0xBF597FC7, 0xC6E00BF3, 0xD5A79147, 0x06CA6351, 0x14292967,
+    0x27B70A85, 0x2E1B2138, 0x4D2C6DFC, 0x53380D13, 0x650A7354, 0x766A0ABB, 0x81C2C92E, 0x92722C85,
+    0xA2BFE8A1, 0xA81A664B, 0xC24B8B70, 0xC76C51A3, 0xD192E819, 0xD6990624, 0xF40E3585, 0x106AA070,
+    0x19A4C116, 0x1E376C08, 0x2748774C, 0x34B0BCB5, 0x391C0CB3, 0x4ED8AA4A, 0x5B9CCA4F, 0x682E6FF3,
+    0x748F82EE, 0x78A5636F, 0x84C87814, 0x8CC70208, 0x90BEFFFA, 0xA4506CEB, 0xBEF9A3F7, 0xC67178F2,
+];
+
+/// First 64 bits of the fractional parts of the cube roots of the first 80 primes.
+const K512: [u64; 80] = [
+    0x428A2F98D728AE22,
+    0x7137449123EF65CD,
+    0xB5C0FBCFEC4D3B2F,
+    0xE9B5DBA58189DBBC,
+    0x3956C25BF348B538,
+    0x59F111F1B605D019,
+    0x923F82A4AF194F9B,
+    0xAB1C5ED5DA6D8118,
+    0xD807AA98A3030242,
+    0x12835B0145706FBE,
+    0x243185BE4EE4B28C,
+    0x550C7DC3D5FFB4E2,
+    0x72BE5D74F27B896F,
+    0x80DEB1FE3B1696B1,
+    0x9BDC06A725C71235,
+    0xC19BF174CF692694,
+    0xE49B69C19EF14AD2,
+    0xEFBE4786384F25E3,
+    0x0FC19DC68B8CD5B5,
+    0x240CA1CC77AC9C65,
+    0x2DE92C6F592B0275,
+    0x4A7484AA6EA6E483,
+    0x5CB0A9DCBD41FBD4,
+    0x76F988DA831153B5,
+    0x983E5152EE66DFAB,
+    0xA831C66D2DB43210,
+    0xB00327C898FB213F,
+    0xBF597FC7BEEF0EE4,
+    0xC6E00BF33DA88FC2,
+    0xD5A79147930AA725,
+    0x06CA6351E003826F,
+    0x142929670A0E6E70,
+    0x27B70A8546D22FFC,
+    0x2E1B21385C26C926,
+    0x4D2C6DFC5AC42AED,
+    0x53380D139D95B3DF,
+    0x650A73548BAF63DE,
+    0x766A0ABB3C77B2A8,
+    0x81C2C92E47EDAEE6,
+    0x92722C851482353B,
+    0xA2BFE8A14CF10364,
+    0xA81A664BBC423001,
+    0xC24B8B70D0F89791,
+    0xC76C51A30654BE30,
+    0xD192E819D6EF5218,
+    0xD69906245565A910,
+    0xF40E35855771202A,
+    0x106AA07032BBD1B8,
+    0x19A4C116B8D2D0C8,
+    0x1E376C085141AB53,
+    0x2748774CDF8EEB99,
+    0x34B0BCB5E19B48A8,
+    0x391C0CB3C5C95A63,
+    0x4ED8AA4AE3418ACB,
+    0x5B9CCA4F7763E373,
+    0x682E6FF3D6B2B8A3,
+    0x748F82EE5DEFB2FC,
+    0x78A5636F43172F60,
+    0x84C87814A1F0AB72,
+    0x8CC702081A6439EC,
+    0x90BEFFFA23631E28,
+    0xA4506CEBDE82BDE9,
+    0xBEF9A3F7B2C67915,
+    0xC67178F2E372532B,
+    0xCA273ECEEA26619C,
+    0xD186B8C721C0C207,
+    0xEADA7DD6CDE0EB1E,
+    0xF57D4F7FEE6ED178,
+    0x06F067AA72176FBA,
+    0x0A637DC5A2C898A6,
+    0x113F9804BEF90DAE,
+    0x1B710B35131C471B,
+    0x28DB77F523047D84,
+    0x32CAAB7B40C72493,
+    0x3C9EBE0A15C9BEBC,
+    0x431D67C49C100D4C,
+    0x4CC5D4BECB3E42B6,
+    0x597F299CFC657E2A,
+    0x5FCB6FAB3AD6FAEC,
+    0x6C44198C4A475817,
+];
+
+// FIPS 180-4 section 5.3, square roots of the first primes for SHA-256 and SHA-512,
+// of the ninth to sixteenth for SHA-224 and SHA-384, SHA-512/256 from the IV generation
+// function of section 5.3.6
+
+const IV224: [u32; 8] = [
+    0xC1059ED8, 0x367CD507, 0x3070DD17, 0xF70E5939, 0xFFC00B31, 0x68581511, 0x64F98FA7, 0xBEFA4FA4,
+];
+
+const IV256: [u32; 8] = [
+    0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A, 0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19,
+];
+
+const IV384: [u64; 8] = [
+    0xCBBB9D5DC1059ED8,
+    0x629A292A367CD507,
+    0x9159015A3070DD17,
+    0x152FECD8F70E5939,
+    0x67332667FFC00B31,
+    0x8EB44A8768581511,
+    0xDB0C2E0D64F98FA7,
+    0x47B5481DBEFA4FA4,
+];
+
+const IV512: [u64; 8] = [
+    0x6A09E667F3BCC908,
+    0xBB67AE8584CAA73B,
+    0x3C6EF372FE94F82B,
+    0xA54FF53A5F1D36F1,
+    0x510E527FADE682D1,
+    0x9B05688C2B3E6C1F,
+    0x1F83D9ABFB41BD6B,
+    0x5BE0CD19137E2179,
+];
+
+const IV512_256: [u64; 8] = [
+    0x22312194FC2BF72C,
+    0x9F555FA3C84C64C2,
+    0x2393B86B6F53B151,
+    0x963877195940EABD,
+    0x96283EE2A88EFFE3,
+    0xBE5E1E2553863992,
+    0x2B0199FC2C85B8AA,
+    0x0EB72DDC81C52CA2,
+];
+
+// The two SHA-2 engines differ only in word size, block size, number of rounds and
+// rotations: big Sigma 0 and 1, then small sigma 0 and 1 whose last amount is a shift.
+macro_rules! engine {
+    ($name:ident, $word:ty, $length:ty, $block:literal, $K:ident,
+     $S0:expr, $S1:expr, $s0:expr, $s1:expr) => {
+        #[derive(Debug, Clone)]
+        struct $name {
+            state: [$word; 8],
+            buffer: [u8; $block],
+            buffered: usize,
+            // total input length in bytes, appended in bits
+            length: $length,
+        }
+
+        impl $name {
+            fn new(state: [$word; 8]) -> Self {
+                Self {
+                    state,
+                    buffer: [0; $block],
+                    buffered: 0,
+                    length: 0,
+                }
+            }
+
+            fn update(&mut self, mut input: &[u8]) {
+                self.length = self.length.wrapping_add(input.len() as $length);
+
+                // top up a partial block first
+                if self.buffered > 0 {
+                    let take = input.len().min($block - self.buffered);
+                    self.buffer[self.buffered..self.buffered + take]
+                        .copy_from_slice(&input[..take]);
+                    self.buffered += take;
+                    input = &input[take..];
+
+                    if self.buffered < $block {
+                        return;
+                    }
+
+                    let block = self.buffer;
+                    self.compress(&block);
+                    self.buffered = 0;
+                }
+
+                let mut blocks = input.chunks_exact($block);
+                for block in blocks.by_ref() {
+                    self.compress(block);
+                }
+
+                let rest = blocks.remainder();
+                self.buffer[..rest.len()].copy_from_slice(rest);
+                self.buffered = rest.len();
+            }
+
+            // the 1 bit, zeros, then the length in bits big endian, filling the last block
+            fn finalize(mut self) -> [$word; 8] {
+                let field = std::mem::size_of::<$length>();
+                let zeros = (2 * $block - self.buffered - 1 - field) % $block;
+
+                let mut padding = vec![0x80_u8];
+                padding.resize(1 + zeros, 0);
+                padding.extend_from_slice(&self.length.wrapping_mul(8).to_be_bytes());
+
+                self.update(&padding);
+
+                self.state
+            }
+
+            fn compress(&mut self, block: &[u8]) {
+                const BYTES: usize = std::mem::size_of::<$word>();
+
+                // message schedule
+                let mut w = [0 as $word; $K.len()];
+                for (word, bytes) in w.iter_mut().zip(block.chunks_exact(BYTES)) {
+                    let mut be = [0; BYTES];
+                    be.copy_from_slice(bytes);
+                    *word = <$word>::from_be_bytes(be);
+                }
+
+                for t in 16..$K.len() {
+                    let [a, b, c] = $s0;
+                    let s0 =
+                        w[t - 15].rotate_right(a) ^ w[t - 15].rotate_right(b) ^ (w[t - 15] >> c);
+                    let [a, b, c] = $s1;
+                    let s1 = w[t - 2].rotate_right(a) ^ w[t - 2].rotate_right(b) ^ (w[t - 2] >> c);
+
+                    w[t] = s1
+                        .wrapping_add(w[t - 7])
+                        .wrapping_add(s0)
+                        .wrapping_add(w[t - 16]);
+                }
+
+                let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.state;
+
+                for t in 0..$K.len() {
+                    let [x, y, z] = $S1;
+                    let sum1 = e.rotate_right(x) ^ e.rotate_right(y) ^ e.rotate_right(z);
+                    let ch = (e & f) ^ (!e & g);
+                    let t1 = h
+                        .wrapping_add(sum1)
+                        .wrapping_add(ch)
+                        .wrapping_add($K[t])
+                        .wrapping_add(w[t]);
+
+                    let [x, y, z] = $S0;
+                    let sum0 = a.rotate_right(x) ^ a.rotate_right(y) ^ a.rotate_right(z);
+                    let maj = (a & b) ^ (a & c) ^ (b & c);
+                    let t2 = sum0.wrapping_add(maj);
+
+                    h = g;
+                    g = f;
+                    f = e;
+                    e = d.wrapping_add(t1);
+                    d = c;
+                    c = b;
+                    b = a;
+                    a = t1.wrapping_add(t2);
+                }
+
+                for (word, value) in self.state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+                    *word = word.wrapping_add(value);
+                }
+            }
+        }
+    };
+}
+
+engine!(
+    Engine256,
+    u32,
+    u64,
+    64,
+    K256,
+    [2, 13, 22],
+    [6, 11, 25],
+    [7, 18, 3],
+    [17, 19, 10]
+);
+engine!(
+    Engine512,
+    u64,
+    u128,
+    128,
+    K512,
+    [28, 34, 39],
+    [14, 18, 41],
+    [1, 8, 7],
+    [19, 61, 6]
+);
+
+// A hash of the family: an engine started from its own initial values, the output cut
+// to `$bytes`. Same API as `Md5`, plus the associated functions `MD5` has.
+macro_rules! variant {
+    ($(#[$doc:meta])* $name:ident, $engine:ident, $iv:ident, $bytes:literal, $size:ty, $block:ty) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone)]
+        pub struct $name {
+            engine: $engine,
+        }
+
+        impl $name {
+            pub fn new() -> Self {
+                Self {
+                    engine: $engine::new($iv),
+                }
+            }
+
+            pub fn update(&mut self, input: &[u8]) {
+                self.engine.update(input);
+            }
+
+            pub fn finalize(self) -> Digest<$bytes> {
+                let bytes = self
+                    .engine
+                    .finalize()
+                    .iter()
+                    .flat_map(|word| word.to_be_bytes())
+                    .collect::<Vec<u8>>();
+
+                let mut digest = [0; $bytes];
+                digest.copy_from_slice(&bytes[..$bytes]);
+
+                Digest(digest)
+            }
+
+            pub fn digest(input: &[u8]) -> Digest<$bytes> {
+                let mut hash = Self::new();
+                hash.update(input);
+
+                hash.finalize()
+            }
+
+            /// Digest of everything `reader` yields, read in chunks in constant memory.
+            pub fn digest_reader<R: Read>(mut reader: R) -> io::Result<Digest<$bytes>> {
+                let mut hash = Self::new();
+                io::copy(&mut reader, &mut hash)?;
+
+                Ok(hash.finalize())
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl Write for $name {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.update(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        impl HashMarker for $name {}
+
+        impl OutputSizeUser for $name {
+            type OutputSize = $size;
+        }
+
+        impl BlockSizeUser for $name {
+            type BlockSize = $block;
+        }
+
+        impl Update for $name {
+            fn update(&mut self, data: &[u8]) {
+                $name::update(self, data);
+            }
+        }
+
+        impl FixedOutput for $name {
+            fn finalize_into(self, out: &mut Output<Self>) {
+                out.copy_from_slice(&$name::finalize(self).0);
+            }
+        }
+
+        impl Reset for $name {
+            fn reset(&mut self) {
+                *self = $name::new();
+            }
+        }
+
+        impl FixedOutputReset for $name {
+            fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+                out.copy_from_slice(&$name::finalize(std::mem::take(self)).0);
+            }
+        }
+    };
+}
+
+variant!(
+    /// SHA-224, SHA-256 with other initial values cut to 224 bits.
+    Sha224, Engine256, IV224, 28, U28, U64
+);
+variant!(
+    /// SHA-256, 32 bit words and 64 rounds.
+    Sha256, Engine256, IV256, 32, U32, U64
+);
+variant!(
+    /// SHA-384, SHA-512 with other initial values cut to 384 bits.
+    Sha384, Engine512, IV384, 48, U48, U128
+);
+variant!(
+    /// SHA-512, 64 bit words and 80 rounds.
+    Sha512, Engine512, IV512, 64, U64, U128
+);
+variant!(
+    /// SHA-512/256, SHA-512 cut to 256 bits, faster than SHA-256 on 64 bit CPUs.
+    Sha512_256, Engine512, IV512_256, 32, U32, U128
+);
@@ -0,0 +1,237 @@
+use num::bigint::BigInt;
+use num::integer::Integer;
+use num::{One, Signed, ToPrimitive, Zero};
+use std::f64::consts::PI;
+
+pub const MAX_DIMENSION: usize = 8;
+
+// gamma_t^t for the Hermite constants of dimensions 0..=8, nu_t can never exceed
+// sqrt(gamma_t) * m^(1/t)
+const HERMITE: [f64; MAX_DIMENSION + 1] =
+    [1.0, 1.0, 4.0 / 3.0, 2.0, 4.0, 8.0, 64.0 / 3.0, 64.0, 256.0];
+
+/// Shortest vector of the dual lattice in `dimension` dimensions: the successive
+/// `dimension`-tuples of the generator lie on parallel hyperplanes `1 / nu` apart.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spectral {
+    pub dimension: usize,
+    pub nu_squared: u128,
+    // Knuth's figure of merit: volume of the ellipsoid x^2 <= nu^2 relative to m
+    pub mu: f64,
+    // nu_t / (sqrt(gamma_t) * m^(1/t)), 1 means the best possible lattice
+    pub merit: f64,
+}
+
+impl Spectral {
+    pub fn nu(&self) -> f64 {
+        (self.nu_squared as f64).sqrt()
+    }
+}
+
+/// Knuth's Algorithm S (TAOCP 3.3.4) for the multiplier `multiplier` modulo `modulus`,
+/// dimensions 2..=`dimensions`. The increment does not change the lattice.
+pub fn spectral_test(modulus: u64, multiplier: u64, dimensions: usize) -> Vec<Spectral> {
+    assert!(modulus > 1, "Modulus {} is too small", modulus);
+    assert!(
+        (2..=MAX_DIMENSION).contains(&dimensions),
+        "Spectral test supports dimensions 2..={}",
+        MAX_DIMENSION
+    );
+    assert!(
+        !(multiplier % modulus).is_zero(),
+        "Multiplier {} is a multiple of modulus {}",
+        multiplier,
+        modulus
+    );
+
+    let m = BigInt::from(modulus);
+    let a = BigInt::from(multiplier % modulus);
+    let mut result = Vec::with_capacity(dimensions - 1);
+
+    // S1, S2: Euclid's algorithm on (m, a), keeping the shortest (u, v) with u = a * v mod m
+    let (mut h, mut h_prev) = (a.clone(), m.clone());
+    let (mut p, mut p_prev) = (BigInt::one(), BigInt::zero());
+    let mut s = BigInt::one() + &a * &a;
+
+    let (mut u, mut v);
+    loop {
+        let q = h_prev.div_floor(&h);
+        u = &h_prev - &q * &h;
+        v = &p_prev - &q * &p;
+
+        if &u * &u + &v * &v >= s {
+            break;
+        }
+
+        s = &u * &u + &v * &v;
+        h_prev = std::mem::replace(&mut h, u.clone());
+        p_prev = std::mem::replace(&mut p, v.clone());
+    }
+
+    // S3: nu_2
+    u -= &h;
+    v -= &p;
+    if &u * &u + &v * &v < s {
+        s = &u * &u + &v * &v;
+        h_prev = u;
+        p_prev = v;
+    }
+
+    result.push(figure(2, &s, modulus));
+
+    // rows of U span the dual lattice, rows of V the lattice itself, U_i . V_j = m * [i = j]
+    let mut us = vec![vec![-&h, p.clone()], vec![-&h_prev, p_prev.clone()]];
+    let mut vs = vec![vec![p_prev.clone(), h_prev.clone()], vec![-&p, -&h]];
+
+    if &p * &h_prev - &h * &p_prev < BigInt::zero() {
+        vs.iter_mut().flatten().for_each(|x| *x = -&*x);
+    }
+
+    let mut r = a.clone();
+
+    for t in 3..=dimensions {
+        // S4: extend the bases by one dimension
+        r = (&a * &r) % &m;
+
+        let mut next = vec![BigInt::zero(); t];
+        next[0] = -&r;
+        next[t - 1] = BigInt::one();
+
+        for (u_i, v_i) in us.iter_mut().zip(vs.iter_mut()) {
+            u_i.push(BigInt::zero());
+
+            let q = round(&(&v_i[0] * &r), &m);
+            v_i.push(&v_i[0] * &r - &q * &m);
+
+            for (x, y) in next.iter_mut().zip(u_i.iter()) {
+                *x += &q * y;
+            }
+        }
+
+        let mut last = vec![BigInt::zero(); t];
+        last[t - 1] = m.clone();
+
+        s = s.min(dot(&next, &next));
+        us.push(next);
+        vs.push(last);
+
+        // S5, S6: pairwise reduction until t - 1 passes in a row change nothing
+        let mut k = t - 1;
+        let mut j = 0;
+        loop {
+            for i in 0..t {
+                let product = dot(&vs[i], &vs[j]);
+                let norm = dot(&vs[j], &vs[j]);
+
+                if i != j && BigInt::from(2) * product.abs() > norm {
+                    let q = round(&product, &norm);
+
+                    for x in 0..t {
+                        let (delta_v, delta_u) = (&q * &vs[j][x], &q * &us[i][x]);
+                        vs[i][x] -= delta_v;
+                        us[j][x] += delta_u;
+                    }
+
+                    s = s.min(dot(&us[j], &us[j]));
+                    k = j;
+                }
+            }
+
+            j = (j + 1) % t;
+            if j == k {
+                break;
+            }
+        }
+
+        // S7: |x_j| <= z_j bounds every dual vector shorter than sqrt(s)
+        let bounds = vs
+            .iter()
+            .map(|v_j| (dot(v_j, v_j) * &s / (&m * &m)).sqrt())
+            .collect::<Vec<_>>();
+
+        // S8 - S10: exhaustive search over the box, lexicographically positive x only
+        let mut x = vec![BigInt::zero(); t];
+        let mut y = vec![BigInt::zero(); t];
+        let mut k = t - 1;
+
+        loop {
+            if x[k] != bounds[k] {
+                x[k] += 1;
+                add(&mut y, &us[k], &BigInt::one());
+
+                for (index, bound) in bounds.iter().enumerate().skip(k + 1) {
+                    x[index] = -bound;
+                    add(&mut y, &us[index], &(BigInt::from(-2) * bound));
+                }
+
+                s = s.min(dot(&y, &y));
+                k = t - 1;
+            } else if k == 0 {
+                break;
+            } else {
+                k -= 1;
+            }
+        }
+
+        result.push(figure(t, &s, modulus));
+    }
+
+    result
+}
+
+/// Multipliers ordered from the best to the worst by their smallest normalized figure of
+/// merit over dimensions 2..=`dimensions`.
+pub fn rank(modulus: u64, multipliers: &[u64], dimensions: usize) -> Vec<(u64, Vec<Spectral>)> {
+    let worst = |results: &Vec<Spectral>| {
+        results
+            .iter()
+            .map(|result| result.merit)
+            .fold(f64::INFINITY, f64::min)
+    };
+
+    let mut ranked = multipliers
+        .iter()
+        .map(|multiplier| (*multiplier, spectral_test(modulus, *multiplier, dimensions)))
+        .collect::<Vec<_>>();
+
+    ranked.sort_by(|(_, left), (_, right)| worst(right).total_cmp(&worst(left)));
+
+    ranked
+}
+
+fn figure(dimension: usize, nu_squared: &BigInt, modulus: u64) -> Spectral {
+    let nu_squared = nu_squared
+        .to_u128()
+        .expect("nu^2 never exceeds 1 + a^2 and fits into u128");
+
+    let t = dimension as f64;
+    let m = modulus as f64;
+    let nu = (nu_squared as f64).sqrt();
+
+    // pi^(t/2) nu^t / ((t/2)! m), with (t/2)! = Gamma(t/2 + 1)
+    let mu =
+        (t / 2.0 * PI.ln() + t * nu.ln() - crate::stats::ln_gamma(t / 2.0 + 1.0) - m.ln()).exp();
+    let merit = nu / (HERMITE[dimension].powf(1.0 / t).sqrt() * m.powf(1.0 / t));
+
+    Spectral {
+        dimension,
+        nu_squared,
+        mu,
+        merit,
+    }
+}
+
+// nearest integer to n / d for d > 0
+fn round(n: &BigInt, d: &BigInt) -> BigInt {
+    (BigInt::from(2) * n + d).div_floor(&(BigInt::from(2) * d))
+}
+
+fn dot(x: &[BigInt], y: &[BigInt]) -> BigInt {
+    x.iter().zip(y).map(|(a, b)| a * b).sum()
+}
+
+fn add(y: &mut [BigInt], x: &[BigInt], factor: &BigInt) {
+    for (target, value) in y.iter_mut().zip(x) {
+        *target += factor * value;
+    }
+}
@@ -0,0 +1,386 @@
+use crate::generator::to_unit;
+use std::f64::consts::{PI, SQRT_2};
+
+pub const SIGNIFICANCE: f64 = 0.01;
+
+#[derive(Debug, Clone)]
+pub struct TestResult {
+    pub name: String,
+    pub statistic: f64,
+    pub p_value: f64,
+}
+
+impl TestResult {
+    pub fn new(name: &str, statistic: f64, p_value: f64) -> Self {
+        Self {
+            name: String::from(name),
+            statistic,
+            p_value,
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.p_value >= SIGNIFICANCE
+    }
+}
+
+/// Runs the whole battery over the first `samples` values of `source`, which are
+/// expected to lie in `0..modulus`, or to cover all of `u64` when `modulus` is `None`.
+pub fn battery<I: Iterator<Item = u64>>(
+    source: I,
+    modulus: Option<u64>,
+    samples: usize,
+) -> Vec<TestResult> {
+    let u = source
+        .take(samples)
+        .map(|x| to_unit(x, modulus))
+        .collect::<Vec<_>>();
+
+    let modulus = modulus.unwrap_or(u64::MAX);
+
+    // never ask for a finer resolution than the generator can produce
+    let cells = |d: u64| d.min(modulus) as usize;
+
+    vec![
+        frequency(&u, cells(64)),
+        serial(&u, cells(16)),
+        gap(&u, 0.0, 0.5, 16),
+        poker(&u, cells(8)),
+        runs(&u),
+        coupon_collector(&u, cells(5), 25),
+        birthday_spacings(&u, modulus.min(1 << 24)),
+    ]
+}
+
+// Equidistribution: d equal cells, chi-square with d - 1 degrees of freedom
+pub fn frequency(u: &[f64], d: usize) -> TestResult {
+    let mut observed = vec![0; d];
+
+    for x in u {
+        observed[cell(*x, d)] += 1;
+    }
+
+    let (statistic, p_value) = chi_square(&observed, &vec![1.0 / d as f64; d]);
+
+    TestResult::new("Frequency", statistic, p_value)
+}
+
+// Non-overlapping pairs falling into d * d cells
+pub fn serial(u: &[f64], d: usize) -> TestResult {
+    let mut observed = vec![0; d * d];
+
+    for pair in u.chunks_exact(2) {
+        observed[cell(pair[0], d) * d + cell(pair[1], d)] += 1;
+    }
+
+    let (statistic, p_value) = chi_square(&observed, &vec![1.0 / (d * d) as f64; d * d]);
+
+    TestResult::new("Serial pairs", statistic, p_value)
+}
+
+// Lengths of gaps between values in [alpha, beta), gaps of t or more lumped together
+pub fn gap(u: &[f64], alpha: f64, beta: f64, t: usize) -> TestResult {
+    let p = beta - alpha;
+    let mut observed = vec![0; t + 1];
+    let mut length = 0;
+
+    for x in u {
+        if (alpha..beta).contains(x) {
+            observed[length.min(t)] += 1;
+            length = 0;
+        } else {
+            length += 1;
+        }
+    }
+
+    let mut probabilities = (0..t)
+        .map(|r| p * (1.0 - p).powi(r as i32))
+        .collect::<Vec<_>>();
+    probabilities.push((1.0 - p).powi(t as i32));
+
+    let (statistic, p_value) = chi_square(&observed, &probabilities);
+
+    TestResult::new("Gap", statistic, p_value)
+}
+
+// Partition test: number of distinct values in hands of five, one and two lumped
+pub fn poker(u: &[f64], d: usize) -> TestResult {
+    let mut observed = vec![0; 4];
+
+    for hand in u.chunks_exact(5) {
+        let mut cards = hand.iter().map(|x| cell(*x, d)).collect::<Vec<_>>();
+        cards.sort();
+        cards.dedup();
+
+        observed[cards.len().max(2) - 2] += 1;
+    }
+
+    // d(d - 1)...(d - r + 1) / d^5 * S(5, r)
+    let probability = |r: usize| {
+        let falling = (0..r).map(|i| d.saturating_sub(i) as f64).product::<f64>();
+
+        falling * stirling(5, r) / (d as f64).powi(5)
+    };
+
+    let probabilities = [
+        probability(1) + probability(2),
+        probability(3),
+        probability(4),
+        probability(5),
+    ];
+
+    let (statistic, p_value) = chi_square(&observed, &probabilities);
+
+    TestResult::new("Poker", statistic, p_value)
+}
+
+// Number of runs up and down, normal approximation
+pub fn runs(u: &[f64]) -> TestResult {
+    let n = u.len() as f64;
+    let mut count = 1;
+    let mut rising = None;
+
+    for pair in u.windows(2) {
+        // ties keep the current direction
+        let direction = match pair[1].partial_cmp(&pair[0]) {
+            Some(std::cmp::Ordering::Greater) => Some(true),
+            Some(std::cmp::Ordering::Less) => Some(false),
+            _ => rising,
+        };
+
+        if rising.is_some() && direction != rising {
+            count += 1;
+        }
+
+        rising = direction;
+    }
+
+    let mean = (2.0 * n - 1.0) / 3.0;
+    let variance = (16.0 * n - 29.0) / 90.0;
+    let z = (count as f64 - mean) / variance.sqrt();
+
+    TestResult::new("Runs up/down", z, erfc(z.abs() / SQRT_2))
+}
+
+// Segment lengths needed to see all d values, lengths of t or more lumped together
+pub fn coupon_collector(u: &[f64], d: usize, t: usize) -> TestResult {
+    let mut observed = vec![0; t - d + 1];
+    let mut seen = vec![false; d];
+    let mut distinct = 0;
+    let mut length = 0;
+
+    for x in u {
+        let value = cell(*x, d);
+        length += 1;
+
+        if !seen[value] {
+            seen[value] = true;
+            distinct += 1;
+        }
+
+        if distinct == d {
+            observed[length.min(t) - d] += 1;
+            seen.iter_mut().for_each(|s| *s = false);
+            distinct = 0;
+            length = 0;
+        }
+    }
+
+    let factorial = (1..=d).map(|i| i as f64).product::<f64>();
+
+    // d! / d^r * S(r - 1, d - 1) for d <= r < t, the rest of the mass for r >= t
+    let mut probabilities = (d..t)
+        .map(|r| factorial / (d as f64).powi(r as i32) * stirling(r - 1, d - 1))
+        .collect::<Vec<_>>();
+    probabilities.push(1.0 - factorial / (d as f64).powi(t as i32 - 1) * stirling(t - 1, d));
+
+    let (statistic, p_value) = chi_square(&observed, &probabilities);
+
+    TestResult::new("Coupon collector", statistic, p_value)
+}
+
+// Marsaglia's birthday spacings: repeated spacings between sorted birthdays in a year
+// of `days`, the total over all years is Poisson distributed
+pub fn birthday_spacings(u: &[f64], days: u64) -> TestResult {
+    // m^3 / 4n = 2 repeats per year, 512 birthdays in 2^24 days
+    let birthdays = ((8 * days) as f64).cbrt().round().max(2.0) as usize;
+    let lambda = (birthdays as f64).powi(3) / (4.0 * days as f64);
+
+    let mut repeats = 0;
+    let mut years = 0;
+
+    for year in u.chunks_exact(birthdays) {
+        let mut dates = year
+            .iter()
+            .map(|x| (x * days as f64) as u64)
+            .collect::<Vec<_>>();
+        dates.sort();
+
+        let mut spacings = dates.windows(2).map(|w| w[1] - w[0]).collect::<Vec<_>>();
+        spacings.sort();
+
+        repeats += spacings.windows(2).filter(|w| w[0] == w[1]).count();
+        years += 1;
+    }
+
+    let mean = lambda * years as f64;
+
+    // two-sided Poisson tail: P(X <= k) = Q(k + 1, mean), P(X >= k) = 1 - Q(k, mean)
+    let lower = igamc(repeats as f64 + 1.0, mean);
+    let upper = if repeats == 0 {
+        1.0
+    } else {
+        1.0 - igamc(repeats as f64, mean)
+    };
+
+    TestResult::new(
+        "Birthday spacings",
+        repeats as f64,
+        (2.0 * lower.min(upper)).min(1.0),
+    )
+}
+
+/// Pearson statistic and its p-value with one degree of freedom less than the number of
+/// possible cells. Cells of probability zero, such as five distinct cards out of fewer
+/// than five values, are left out.
+pub fn chi_square(observed: &[u64], probabilities: &[f64]) -> (f64, f64) {
+    let n = observed.iter().sum::<u64>() as f64;
+
+    let cells = observed
+        .iter()
+        .zip(probabilities)
+        .filter(|(_, p)| **p > 0.0)
+        .collect::<Vec<_>>();
+
+    let statistic = cells
+        .iter()
+        .map(|(o, p)| (**o as f64 - n * **p).powi(2) / (n * **p))
+        .sum::<f64>();
+
+    let freedom = (cells.len() - 1) as f64;
+
+    (statistic, igamc(freedom / 2.0, statistic / 2.0))
+}
+
+/// Regularized upper incomplete gamma function Q(a, x).
+pub fn igamc(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+
+    if x < a + 1.0 {
+        // series for P(a, x)
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        let mut denominator = a;
+
+        for _ in 0..1000 {
+            denominator += 1.0;
+            term *= x / denominator;
+            sum += term;
+
+            if term.abs() < sum.abs() * f64::EPSILON {
+                break;
+            }
+        }
+
+        1.0 - sum * (-x + a * x.ln() - ln_gamma(a)).exp()
+    } else {
+        // Lentz's continued fraction for Q(a, x)
+        let tiny = f64::MIN_POSITIVE / f64::EPSILON;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+
+        for i in 1..1000 {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+
+            d = an * d + b;
+            if d.abs() < tiny {
+                d = tiny;
+            }
+
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+
+            if (delta - 1.0).abs() < f64::EPSILON {
+                break;
+            }
+        }
+
+        (-x + a * x.ln() - ln_gamma(a)).exp() * h
+    }
+}
+
+/// Complementary error function, erfc(x) = Q(1/2, x^2) for x >= 0.
+pub fn erfc(x: f64) -> f64 {
+    if x < 0.0 {
+        2.0 - erfc(-x)
+    } else {
+        igamc(0.5, x * x)
+    }
+}
+
+/// Standard normal cumulative distribution function.
+pub fn normal_cdf(z: f64) -> f64 {
+    0.5 * erfc(-z / SQRT_2)
+}
+
+// Lanczos approximation, g = 7
+pub fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    if x < 0.5 {
+        // reflection formula
+        (PI / (PI * x).sin()).ln() - ln_gamma(1.0 - x)
+    } else {
+        let x = x - 1.0;
+        let t = x + 7.5;
+
+        let sum = COEFFICIENTS[1..]
+            .iter()
+            .enumerate()
+            .fold(COEFFICIENTS[0], |acc, (i, c)| {
+                acc + c / (x + i as f64 + 1.0)
+            });
+
+        0.5 * (2.0 * PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+    }
+}
+
+// Stirling number of the second kind S(n, k)
+fn stirling(n: usize, k: usize) -> f64 {
+    let mut row = vec![0.0; k + 1];
+    row[0] = 1.0;
+
+    for _ in 0..n {
+        for j in (1..=k).rev() {
+            row[j] = j as f64 * row[j] + row[j - 1];
+        }
+        row[0] = 0.0;
+    }
+
+    row[k]
+}
+
+fn cell(x: f64, d: usize) -> usize {
+    ((x * d as f64) as usize).min(d - 1)
+}
@@ -1,11 +1,156 @@
+use crate::lcg::Word;
 use std::cmp::Eq;
 use std::collections::HashSet;
 use std::hash::Hash;
 
-pub fn unique<T: Eq + Hash>(sequence: &Vec<T>) -> usize {
-    sequence
-        .into_iter()
-        .map(|x| x)
-        .collect::<HashSet<_>>()
-        .len()
+pub fn unique<T: Eq + Hash>(sequence: &[T]) -> usize {
+    sequence.iter().collect::<HashSet<_>>().len()
+}
+
+/// Deterministic Miller-Rabin, the first twelve primes as bases cover all of `u64`.
+pub fn is_prime(n: u64) -> bool {
+    const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+    if n < 2 {
+        return false;
+    }
+
+    for p in BASES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+
+    // n - 1 = d * 2^s with d odd
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+
+    'witness: for a in BASES {
+        let mut x = pow_mod(a, d, n);
+
+        if x == 1 || x == n - 1 {
+            continue;
+        }
+
+        for _ in 1..s {
+            x = (x as u128 * x as u128 % n as u128) as u64;
+
+            if x == n - 1 {
+                continue 'witness;
+            }
+        }
+
+        return false;
+    }
+
+    true
+}
+
+pub fn pow_mod(base: u64, mut exponent: u64, modulus: u64) -> u64 {
+    let m = modulus as u128;
+    let mut base = base as u128 % m;
+    let mut result = 1 % m;
+
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result * base % m;
+        }
+
+        base = base * base % m;
+        exponent >>= 1;
+    }
+
+    result as u64
+}
+
+/// `a * b mod m` for any operands, without overflow.
+pub fn mul_mod<W: Word>(a: W, b: W, m: W) -> W {
+    W::mul_add_mod(a, b, W::zero(), m)
+}
+
+// a + b mod m for a and b below m
+pub fn add_mod<W: Word>(a: W, b: W, m: W) -> W {
+    if a >= m - b {
+        a - (m - b)
+    } else {
+        a + b
+    }
+}
+
+// a - b mod m for a and b below m
+pub fn sub_mod<W: Word>(a: W, b: W, m: W) -> W {
+    if a >= b {
+        a - b
+    } else {
+        m - (b - a)
+    }
+}
+
+/// Inverse of `a` modulo `m` by the extended Euclidean algorithm, the coefficients are
+/// kept modulo `m` so nothing goes negative.
+pub fn mod_inverse<W: Word>(a: W, m: W) -> Option<W> {
+    let (mut r0, mut r1) = (m, a % m);
+    let (mut t0, mut t1) = (W::zero(), W::one() % m);
+
+    while !r1.is_zero() {
+        let q = r0 / r1;
+
+        (r0, r1) = (r1, r0 - q * r1);
+        (t0, t1) = (t1, sub_mod(t0, mul_mod(q % m, t1, m), m));
+    }
+
+    (r0 == W::one()).then_some(t0)
+}
+
+/// Compares all bytes whatever the first difference, so the time taken does not tell
+/// an attacker how much of a secret tag was right. Only the lengths leak.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .fold(0, |difference, (x, y)| difference | (x ^ y))
+            == 0
+}
+
+/// Bytes of a hex string in either case, `None` for odd lengths and other characters.
+pub fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    // from_str_radix alone would take a sign, "+f" is not a byte
+    if !hex.len().is_multiple_of(2) || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// Lowercase hex of arbitrary bytes, the inverse of `from_hex`.
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Standard alphabet with `=` padding, as in RFC 4648.
+pub fn to_base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0_u32, |acc, (i, byte)| acc | (*byte as u32) << (16 - 8 * i));
+
+        // n bytes fill n + 1 sextets, the rest is padding
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(group >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
 }
@@ -0,0 +1,142 @@
+use crate::generator::{fill_bytes, splitmix64, Generator};
+use rand::{RngCore, SeedableRng};
+
+/// Marsaglia's xorshift64 with the (13, 7, 17) triple, period 2^64 - 1. The state must
+/// never be zero, zero is the one fixed point of the shifts.
+#[derive(Debug, Clone)]
+pub struct Xorshift64 {
+    state: u64,
+}
+
+impl Xorshift64 {
+    pub fn new(seed: u64) -> Self {
+        assert!(seed != 0, "Xorshift seed must not be zero");
+
+        Self { state: seed }
+    }
+}
+
+impl RngCore for Xorshift64 {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.state = x;
+
+        x
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        fill_bytes(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl Generator for Xorshift64 {
+    fn modulus(&self) -> Option<u64> {
+        None
+    }
+}
+
+impl Iterator for Xorshift64 {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_u64())
+    }
+}
+
+/// Blackman and Vigna's xoshiro256**, period 2^256 - 1.
+#[derive(Debug, Clone)]
+pub struct Xoshiro256 {
+    state: [u64; 4],
+}
+
+impl Xoshiro256 {
+    pub fn new(state: [u64; 4]) -> Self {
+        assert!(state != [0; 4], "Xoshiro state must not be all zeros");
+
+        Self { state }
+    }
+}
+
+impl RngCore for Xoshiro256 {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let s = &mut self.state;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+
+        result
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        fill_bytes(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// A `u64` seed is expanded with SplitMix64 as the authors recommend, an all zero
+/// seed is replaced by the expansion of zero.
+impl SeedableRng for Xoshiro256 {
+    type Seed = [u8; 32];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        if seed == [0; 32] {
+            return Self::seed_from_u64(0);
+        }
+
+        let mut state = [0; 4];
+        for (word, bytes) in state.iter_mut().zip(seed.chunks_exact(8)) {
+            *word = u64::from_le_bytes(bytes.try_into().expect("chunk of 8 bytes"));
+        }
+
+        Self::new(state)
+    }
+
+    fn seed_from_u64(mut seed: u64) -> Self {
+        let mut state = [0; 4];
+        for word in state.iter_mut() {
+            *word = splitmix64(&mut seed);
+        }
+
+        Self::new(state)
+    }
+}
+
+impl Generator for Xoshiro256 {
+    fn modulus(&self) -> Option<u64> {
+        None
+    }
+}
+
+impl Iterator for Xoshiro256 {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_u64())
+    }
+}
@@ -0,0 +1,116 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn run(args: &[&str]) -> Vec<u8> {
+    let output = Command::new(env!("CARGO_BIN_EXE_carapace"))
+        .args(args)
+        .output()
+        .expect("Unable to run carapace");
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    output.stdout
+}
+
+#[test]
+fn binary_output_is_payload_only() {
+    let lcg = ["-lcg", "2^11", "3^5", "1", "4", "-n", "100"];
+
+    let decimal = String::from_utf8(run(&lcg)).unwrap();
+    let expected = decimal
+        .lines()
+        .next()
+        .unwrap()
+        .split_whitespace()
+        .map(|x| x.parse::<u32>().unwrap())
+        .collect::<Vec<u32>>();
+
+    // the reports of -u, -period and -stats must not end up among the numbers
+    let reports = ["-format", "binary", "-u", "-period", "-stats"];
+    let binary = run(&[&lcg[..], &reports[..]].concat());
+
+    assert_eq!(binary.len(), 100 * 4);
+    assert_eq!(
+        binary
+            .chunks(4)
+            .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+            .collect::<Vec<u32>>(),
+        expected
+    );
+
+    // a modulus above 2^32 takes 8 bytes per value
+    let lcg = ["-lcg", "2^63", "6364136223846793005", "1", "0", "-n", "100"];
+    let reports = ["-format", "binary", "-u", "-stats"];
+
+    assert_eq!(run(&[&lcg[..], &reports[..]].concat()).len(), 100 * 8);
+}
+
+#[test]
+fn csv_output_is_payload_only() {
+    let csv = run(&[
+        "-lcg", "2^11", "3^5", "1", "4", "-n", "10", "-format", "csv", "-u", "-period",
+    ]);
+    let csv = String::from_utf8(csv).unwrap();
+
+    // the header and one row per value
+    assert_eq!(csv.lines().count(), 11);
+    assert!(csv.lines().skip(1).all(|row| row.split(',').count() == 2));
+}
+
+#[test]
+fn period_search_is_capped() {
+    let stderr = Command::new(env!("CARGO_BIN_EXE_carapace"))
+        .args([
+            "-lcg",
+            "2^63",
+            "6364136223846793005",
+            "1",
+            "0",
+            "-n",
+            "1",
+            "-period",
+            "100000",
+            "-format",
+            "lines",
+        ])
+        .output()
+        .expect("Unable to run carapace")
+        .stderr;
+
+    assert!(String::from_utf8(stderr)
+        .unwrap()
+        .contains("Tail plus cycle length - at least 33333"));
+}
+
+#[test]
+fn hmac_follows_the_hash_option() {
+    let tag = |hash: &str| {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_carapace"))
+            .args([hash, "-hmac", "Jefe", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Unable to run carapace");
+
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(b"what do ya want for nothing?")
+            .unwrap();
+
+        String::from_utf8(child.wait_with_output().unwrap().stdout).unwrap()
+    };
+
+    // RFC 2202 and RFC 4231 test case 2
+    assert_eq!(tag("-md5"), "750C783E6AB0B503EAA86E310A5DB738");
+    assert_eq!(tag("-sha1"), "EFFCDF6AE5EB2FA2D27416D5F184DF9C259A7C79");
+    assert_eq!(
+        tag("-sha256"),
+        "5BDCC146BF60754E6A042426089575C75A003F089D2739839DEC58B964EC3843"
+    );
+}